@@ -30,9 +30,13 @@ impl PackageInfo {
     }
 
     pub fn full_name(&self) -> String {
+        let epoch_prefix = match self.epoch.as_str() {
+            "" | "0" => String::new(),
+            epoch => format!("{}:", epoch),
+        };
         format!(
-            "{}-{}-{}.{}",
-            self.name, self.version, self.release, self.arch
+            "{}{}-{}-{}.{}",
+            epoch_prefix, self.name, self.version, self.release, self.arch
         )
     }
 
@@ -41,6 +45,204 @@ impl PackageInfo {
             && (pkg_info.kind == PackageType::BinaryPackage)
             && (self.source_pkg == pkg_info.source_pkg)
     }
+
+    /// Compares this package's epoch/version/release against `other`'s, newest-last, the
+    /// same way RPM's `rpmvercmp`/EVR comparison does: epoch (numeric, missing = 0) first,
+    /// then version, then release, each compared with [`rpmvercmp`].
+    pub fn compare_evr(&self, other: &PackageInfo) -> std::cmp::Ordering {
+        let self_epoch = self.epoch.parse::<u64>().unwrap_or(0);
+        let other_epoch = other.epoch.parse::<u64>().unwrap_or(0);
+
+        self_epoch
+            .cmp(&other_epoch)
+            .then_with(|| rpmvercmp(&self.version, &other.version))
+            .then_with(|| rpmvercmp(&self.release, &other.release))
+    }
+
+    /// Picks the newest-EVR package among `candidates` for the same name/arch, so a target
+    /// resolved from a directory containing several versions of the same package (e.g. an
+    /// old and a newly-installed debuginfo rpm) deterministically picks the latest one
+    /// instead of whichever the directory listing happened to return first.
+    pub fn pick_newest<'a>(candidates: &'a [PackageInfo]) -> Option<&'a PackageInfo> {
+        candidates
+            .iter()
+            .max_by(|lhs, rhs| lhs.compare_evr(rhs))
+    }
+}
+
+/// A maximal run of digits or of ASCII letters, the atomic unit `rpmvercmp` compares.
+enum VerSegment<'a> {
+    Digits(&'a str),
+    Alpha(&'a str),
+}
+
+/// Splits off the next digit/alpha run from `s`, skipping any leading non-alphanumeric
+/// separator characters first. Returns `None` once `s` is exhausted.
+fn next_segment(s: &str) -> Option<(VerSegment<'_>, &str)> {
+    let s = s.trim_start_matches(|c: char| !c.is_ascii_alphanumeric());
+    if s.is_empty() {
+        return None;
+    }
+
+    let is_digit = s.as_bytes()[0].is_ascii_digit();
+    let end = s
+        .find(|c: char| c.is_ascii_alphanumeric() != is_digit || !c.is_ascii_alphanumeric())
+        .unwrap_or(s.len());
+    let (segment, rest) = s.split_at(end);
+
+    Some((
+        match is_digit {
+            true => VerSegment::Digits(segment),
+            false => VerSegment::Alpha(segment),
+        },
+        rest,
+    ))
+}
+
+/// Implements RPM's `rpmvercmp` algorithm for comparing a single version or release string.
+///
+/// Walks both strings in lockstep, comparing one digit/alpha segment at a time (skipping
+/// non-alphanumeric separators): a `~` sorts before everything, including the empty string;
+/// a `^` sorts after the end of a string but before any further segment; numeric segments
+/// compare numerically (after stripping leading zeros, with the longer run winning ties) and
+/// always outrank alpha segments; alpha segments compare lexically; whichever string runs out
+/// of segments first is older, unless the other string's next character is `~`.
+fn rpmvercmp(lhs: &str, rhs: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let mut lhs_rest = lhs;
+    let mut rhs_rest = rhs;
+
+    loop {
+        // Skip separators, but not `~`/`^`, which carry an ordering of their own.
+        let is_separator = |c: char| !c.is_ascii_alphanumeric() && c != '~' && c != '^';
+        lhs_rest = lhs_rest.trim_start_matches(is_separator);
+        rhs_rest = rhs_rest.trim_start_matches(is_separator);
+
+        // `~` sorts before everything, including the end of the string.
+        let lhs_tilde = lhs_rest.starts_with('~');
+        let rhs_tilde = rhs_rest.starts_with('~');
+        match (lhs_tilde, rhs_tilde) {
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (true, true) => {
+                lhs_rest = &lhs_rest[1..];
+                rhs_rest = &rhs_rest[1..];
+                continue;
+            }
+            (false, false) => {}
+        }
+
+        // `^` sorts after the end of string, but before any following segment.
+        let lhs_caret = lhs_rest.starts_with('^');
+        let rhs_caret = rhs_rest.starts_with('^');
+        match (lhs_caret, rhs_caret) {
+            (true, false) => {
+                return match rhs_rest.is_empty() {
+                    true => Ordering::Greater,
+                    false => Ordering::Less,
+                };
+            }
+            (false, true) => {
+                return match lhs_rest.is_empty() {
+                    true => Ordering::Less,
+                    false => Ordering::Greater,
+                };
+            }
+            (true, true) => {
+                lhs_rest = &lhs_rest[1..];
+                rhs_rest = &rhs_rest[1..];
+                continue;
+            }
+            (false, false) => {}
+        }
+
+        let lhs_segment = next_segment(lhs_rest);
+        let rhs_segment = next_segment(rhs_rest);
+
+        let (lhs_seg, rhs_seg) = match (lhs_segment, rhs_segment) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+
+        let ordering = match (lhs_seg.0, rhs_seg.0) {
+            (VerSegment::Digits(lhs), VerSegment::Digits(rhs)) => {
+                let lhs = lhs.trim_start_matches('0');
+                let rhs = rhs.trim_start_matches('0');
+                lhs.len().cmp(&rhs.len()).then_with(|| lhs.cmp(rhs))
+            }
+            (VerSegment::Alpha(lhs), VerSegment::Alpha(rhs)) => lhs.cmp(rhs),
+            (VerSegment::Digits(_), VerSegment::Alpha(_)) => Ordering::Greater,
+            (VerSegment::Alpha(_), VerSegment::Digits(_)) => Ordering::Less,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        lhs_rest = lhs_seg.1;
+        rhs_rest = rhs_seg.1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn tilde_sorts_before_empty_string() {
+        // A "~" segment sorts before everything, including the end of the other string.
+        assert_eq!(rpmvercmp("1.0~rc1", "1.0"), Ordering::Less);
+        assert_eq!(rpmvercmp("1.0", "1.0~rc1"), Ordering::Greater);
+    }
+
+    #[test]
+    fn caret_sorts_after_end_of_string() {
+        // A "^" segment sorts after the end of the other string, but before any further
+        // segment that follows it.
+        assert_eq!(rpmvercmp("1.0^", "1.0"), Ordering::Greater);
+        assert_eq!(rpmvercmp("1.0", "1.0^"), Ordering::Less);
+        assert_eq!(rpmvercmp("1.0^", "1.0^git1"), Ordering::Less);
+    }
+
+    #[test]
+    fn digit_segment_outranks_alpha_segment() {
+        // Numeric segments always outrank alpha segments, regardless of their contents.
+        assert_eq!(rpmvercmp("1.0", "1.a"), Ordering::Greater);
+        assert_eq!(rpmvercmp("1.a", "1.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn numeric_comparison_strips_leading_zeros() {
+        // Leading zeros are stripped before comparing numerically, so "007" == "7" (not a
+        // longer-run win for "007"), and the stripped values are then compared normally.
+        assert_eq!(rpmvercmp("1.007", "1.7"), Ordering::Equal);
+        assert_eq!(rpmvercmp("1.007", "1.8"), Ordering::Less);
+        assert_eq!(rpmvercmp("1.05", "1.10"), Ordering::Less);
+    }
+
+    #[test]
+    fn compare_evr_considers_epoch_first() {
+        let older = PackageInfo {
+            name: "foo".to_string(),
+            kind: PackageType::BinaryPackage,
+            arch: "x86_64".to_string(),
+            epoch: "0".to_string(),
+            version: "2.0".to_string(),
+            release: "1".to_string(),
+            license: String::new(),
+            source_pkg: String::new(),
+        };
+        let newer = PackageInfo {
+            epoch: "1".to_string(),
+            version: "1.0".to_string(),
+            ..older.clone()
+        };
+
+        assert_eq!(older.compare_evr(&newer), Ordering::Less);
+    }
 }
 
 impl std::fmt::Display for PackageInfo {
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use clap::Parser;
 
 use crate::constants::*;
+use crate::package::{CompressionFormat, DEFAULT_COMPRESSION_WINDOW_MIB};
 use crate::util::sys;
 
 #[derive(Parser, Debug)]
@@ -80,6 +81,18 @@ pub struct CliArguments {
     #[arg(short, long, default_value=CLI_DEFAULT_VERBOSE_FLAG)]
     pub verbose: bool,
 
+    /// Patch package compression format
+    #[arg(long, value_enum, default_value_t=CompressionFormat::Xz)]
+    pub compression_format: CompressionFormat,
+
+    /// Patch package compression level (0-9)
+    #[arg(long, default_value_t=6)]
+    pub compression_level: u32,
+
+    /// Xz compression dictionary/window size, in MiB
+    #[arg(long, default_value_t=DEFAULT_COMPRESSION_WINDOW_MIB)]
+    pub compression_window: u32,
+
     /// Patch file(s)
     #[arg(required=true)]
     pub patches: Vec<PathBuf>
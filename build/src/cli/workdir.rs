@@ -1,3 +1,5 @@
+use anyhow::{Context, Result};
+
 use crate::util::sys;
 use crate::util::fs;
 
@@ -9,20 +11,25 @@ struct WorkDir {
 }
 
 impl WorkDir {
-    pub fn new(base_dir: &str) -> std::io::Result<Self> {
+    pub fn new(base_dir: &str) -> Result<Self> {
         let process_id    = sys::get_process_id();
         let process_name  = sys::get_process_name();
-        let base_dir_path = fs::realpath(base_dir)?;
+        let base_dir_path = fs::realpath(base_dir)
+            .with_context(|| format!("Failed to resolve real path of \"{}\"", base_dir))?;
 
         let work_dir           = format!("{}/{}.{}", base_dir_path.display(), process_id, process_name);
         let patch_build_root   = format!("{}/patch_root",   work_dir);
         let patch_output_dir   = format!("{}/patch_output", patch_build_root);
         let package_build_root = format!("{}/pkg_root",     work_dir);
 
-        fs::create_dir_all(&work_dir)?;
-        fs::create_dir(&patch_build_root)?;
-        fs::create_dir(&patch_output_dir)?;
-        fs::create_dir(&package_build_root)?;
+        fs::create_dir_all(&work_dir)
+            .with_context(|| format!("Failed to create directory \"{}\"", work_dir))?;
+        fs::create_dir(&patch_build_root)
+            .with_context(|| format!("Failed to create directory \"{}\"", patch_build_root))?;
+        fs::create_dir(&patch_output_dir)
+            .with_context(|| format!("Failed to create directory \"{}\"", patch_output_dir))?;
+        fs::create_dir(&package_build_root)
+            .with_context(|| format!("Failed to create directory \"{}\"", package_build_root))?;
 
         Ok(Self {
             work_dir,
@@ -32,8 +39,9 @@ impl WorkDir {
         })
     }
 
-    pub fn clear(&self) -> std::io::Result<()> {
-        std::fs::remove_dir_all(&self.work_dir)
+    pub fn clear(&self) -> Result<()> {
+        common::util::fs::remove_dir_all_robust(&self.work_dir)
+            .with_context(|| format!("Failed to remove directory \"{}\"", self.work_dir))
     }
 }
 
@@ -68,14 +76,15 @@ impl CliWorkDir {
 }
 
 impl CliWorkDir {
-    pub fn create(&mut self, base_dir: &str) -> std::io::Result<()> {
-        fs::check_dir(base_dir)?;
+    pub fn create(&mut self, base_dir: &str) -> Result<()> {
+        fs::check_dir(base_dir)
+            .with_context(|| format!("\"{}\" is not a valid directory", base_dir))?;
         self.inner = Some(WorkDir::new(base_dir)?);
 
         Ok(())
     }
 
-    pub fn clean_all(&mut self) -> std::io::Result<()> {
+    pub fn clean_all(&mut self) -> Result<()> {
         self.get_inner().clear()?;
 
         Ok(())
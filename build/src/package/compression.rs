@@ -0,0 +1,98 @@
+use std::io::Write;
+use std::path::Path;
+
+use clap::ValueEnum;
+use xz2::stream::{Check, LzmaOptions, Stream};
+use xz2::write::XzEncoder;
+
+/// Default LZMA dictionary/window size used for `xz` packages. The stock `xz` default is
+/// about 8 MiB; a 64 MiB window shrinks archives of many similar object/patch files
+/// noticeably, at the cost of higher peak memory while compressing and decompressing.
+pub const DEFAULT_COMPRESSION_WINDOW_MIB: u32 = 64;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    Gzip,
+    Xz,
+    None,
+}
+
+impl Default for CompressionFormat {
+    fn default() -> Self {
+        CompressionFormat::Xz
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionOptions {
+    pub format: CompressionFormat,
+    pub level: u32,
+    pub window_mib: u32,
+}
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        Self {
+            format: CompressionFormat::default(),
+            level: 6,
+            window_mib: DEFAULT_COMPRESSION_WINDOW_MIB,
+        }
+    }
+}
+
+impl CompressionOptions {
+    /// Archive every file under `source_dir` into `output_path`, compressed according to
+    /// these options. Returns the final archive path (with the format-appropriate
+    /// extension appended).
+    pub fn pack_tarball<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        source_dir: P,
+        output_path: Q,
+    ) -> std::io::Result<String> {
+        let source_dir = source_dir.as_ref();
+        let output_path = output_path.as_ref();
+
+        match self.format {
+            CompressionFormat::Gzip => {
+                let archive_path = format!("{}.tar.gz", output_path.display());
+                let file = std::fs::File::create(&archive_path)?;
+                let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::new(self.level));
+                Self::write_tar(encoder, source_dir)?;
+                Ok(archive_path)
+            }
+            CompressionFormat::Xz => {
+                let archive_path = format!("{}.tar.xz", output_path.display());
+                let file = std::fs::File::create(&archive_path)?;
+                let encoder = self.xz_encoder(file)?;
+                Self::write_tar(encoder, source_dir)?;
+                Ok(archive_path)
+            }
+            CompressionFormat::None => {
+                let archive_path = format!("{}.tar", output_path.display());
+                let file = std::fs::File::create(&archive_path)?;
+                Self::write_tar(file, source_dir)?;
+                Ok(archive_path)
+            }
+        }
+    }
+
+    fn xz_encoder(&self, file: std::fs::File) -> std::io::Result<XzEncoder<std::fs::File>> {
+        let dict_size = self.window_mib.saturating_mul(1024 * 1024);
+
+        let mut lzma_options = LzmaOptions::new_preset(self.level).map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+        })?;
+        lzma_options.dict_size(dict_size);
+
+        let stream = Stream::new_easy_encoder_with_options(&lzma_options, Check::Crc64)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+
+        Ok(XzEncoder::new_stream(file, stream))
+    }
+
+    fn write_tar<W: Write>(writer: W, source_dir: &Path) -> std::io::Result<()> {
+        let mut archive = tar::Builder::new(writer);
+        archive.append_dir_all(".", source_dir)?;
+        archive.finish()
+    }
+}
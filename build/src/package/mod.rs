@@ -1,3 +1,4 @@
+mod compression;
 mod package_info;
 mod rpm_extractor;
 mod rpm_helper;
@@ -5,7 +6,9 @@ mod rpm_spec_helper;
 mod rpm_spec_generator;
 mod rpm_spec_parser;
 mod rpm_builder;
+mod tarball_package_generator;
 
+pub use compression::*;
 pub use package_info::*;
 pub use rpm_extractor::*;
 pub use rpm_spec_helper::*;
@@ -0,0 +1,148 @@
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+
+use crate::constants::*;
+use crate::util::fs;
+
+use crate::package::CompressionOptions;
+use crate::patch::PatchInfo;
+
+/// Alternative to `RpmSpecGenerator` that packages a patch as a self-contained archive
+/// instead of an RPM spec, mirroring the combiner/scripter/tarballer split used by the
+/// rust-installer ecosystem: files are laid out under a manifest directory, a
+/// `components`/`manifest.in` pair describes what goes where, and `install.sh`/
+/// `uninstall.sh` replace the `%install`/`%preun` spec stanzas.
+pub struct TarballPackageGenerator;
+
+impl TarballPackageGenerator {
+    #[inline(always)]
+    fn get_patch_name(patch_info: &PatchInfo) -> &str {
+        patch_info.get_patch().get_name()
+    }
+
+    fn write_components<W: Write>(mut writer: W, file_names: &[String]) -> std::io::Result<()> {
+        for file_name in file_names {
+            writeln!(writer, "{}", file_name)?;
+        }
+        writer.flush()
+    }
+
+    fn write_manifest<W: Write>(
+        mut writer: W,
+        patch_name: &str,
+        file_names: &[String],
+    ) -> std::io::Result<()> {
+        let pkg_install_path = format!("{}/{}", PATCH_INSTALL_PATH, patch_name);
+
+        writeln!(writer, "dir:{}:{}", PATCH_DIR_PERMISSION, pkg_install_path)?;
+        for file_name in file_names {
+            writeln!(
+                writer,
+                "file:{}:{}/{}:{}",
+                PATCH_FILE_PERMISSION, pkg_install_path, file_name, file_name
+            )?;
+        }
+        writer.flush()
+    }
+
+    fn write_install_script<W: Write>(
+        mut writer: W,
+        patch_name: &str,
+        file_names: &[String],
+    ) -> std::io::Result<()> {
+        let pkg_install_path = format!("{}/{}", PATCH_INSTALL_PATH, patch_name);
+
+        writeln!(writer, "#!/bin/sh")?;
+        writeln!(writer, "set -e")?;
+        writeln!(writer, "SELF_DIR=$(cd \"$(dirname \"$0\")\" && pwd)")?;
+        writeln!(writer, "install -m {} -d \"{}\"", PATCH_DIR_PERMISSION, pkg_install_path)?;
+        for file_name in file_names {
+            writeln!(
+                writer,
+                "install -m {} \"$SELF_DIR/{}\" \"{}/{}\"",
+                PATCH_FILE_PERMISSION, file_name, pkg_install_path, file_name
+            )?;
+        }
+        writer.flush()
+    }
+
+    fn write_uninstall_script<W: Write>(mut writer: W, patch_name: &str) -> std::io::Result<()> {
+        let pkg_install_path = format!("{}/{}", PATCH_INSTALL_PATH, patch_name);
+
+        writeln!(writer, "#!/bin/sh")?;
+        writeln!(writer, "set -e")?;
+        writeln!(writer, "rm -rf \"{}\"", pkg_install_path)?;
+        writer.flush()
+    }
+
+    /// Lay `source_dir`'s patch files out under `output_dir/<patch_name>` together with a
+    /// `components` manifest, a `manifest.in` (the tarball equivalent of the RPM
+    /// `%install` stanza) and `install.sh`/`uninstall.sh`, then return the manifest
+    /// directory so the caller can archive it.
+    pub fn generate_from_patch_info(
+        patch_info: &PatchInfo,
+        source_dir: &str,
+        output_dir: &str,
+    ) -> std::io::Result<String> {
+        fs::check_dir(source_dir)?;
+        fs::check_dir(output_dir)?;
+
+        let patch_name = Self::get_patch_name(patch_info);
+        let manifest_dir = format!("{}/{}", output_dir, patch_name);
+        std::fs::create_dir_all(&manifest_dir)?;
+
+        let file_names = fs::list_all_files(source_dir, true)?
+            .into_iter()
+            .map(fs::file_name)
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+
+        for file_name in &file_names {
+            std::fs::copy(
+                format!("{}/{}", source_dir, file_name),
+                format!("{}/{}", manifest_dir, file_name),
+            )?;
+        }
+
+        Self::write_components(
+            std::fs::File::create(format!("{}/components", manifest_dir))?,
+            &file_names,
+        )?;
+        Self::write_manifest(
+            std::fs::File::create(format!("{}/manifest.in", manifest_dir))?,
+            patch_name,
+            &file_names,
+        )?;
+        Self::write_install_script(
+            std::fs::File::create(format!("{}/install.sh", manifest_dir))?,
+            patch_name,
+            &file_names,
+        )?;
+        Self::write_uninstall_script(
+            std::fs::File::create(format!("{}/uninstall.sh", manifest_dir))?,
+            patch_name,
+        )?;
+
+        for script in ["install.sh", "uninstall.sh"] {
+            let script_path = format!("{}/{}", manifest_dir, script);
+            std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755))?;
+        }
+
+        Ok(manifest_dir)
+    }
+
+    /// Compress a manifest directory produced by `generate_from_patch_info` into a single
+    /// archive under `output_dir`, using `compression` to pick the format/level and, for
+    /// `xz`, the LZMA dictionary window.
+    pub fn archive(
+        manifest_dir: &str,
+        patch_info: &PatchInfo,
+        output_dir: &str,
+        compression: &CompressionOptions,
+    ) -> std::io::Result<String> {
+        let patch_name = Self::get_patch_name(patch_info);
+        let output_path = format!("{}/{}", output_dir, patch_name);
+
+        compression.pack_tarball(manifest_dir, &output_path)
+    }
+}
@@ -9,6 +9,8 @@ use crate::patch::{PatchBuilder, PatchBuilderArgumentsParser, PatchBuilderArgume
 use super::kpatch_helper::KernelPatchHelper;
 use super::kpatch_builder_args::KernelPatchBuilderArguments;
 
+use crate::patch::kpatch_helper::KernelPatchHelper as KernelPatchDiffHelper;
+
 pub struct KernelPatchBuilder;
 
 impl KernelPatchBuilder {
@@ -58,6 +60,17 @@ impl PatchBuilderArgumentsParser for KernelPatchBuilder {
         let kernel_source_dir = RpmHelper::find_source_directory(source_pkg_build_dir, patch_info)?;
         debug!("source directory: '{}'", kernel_source_dir);
 
+        let patch_file_paths: Vec<std::path::PathBuf> = patch_info
+            .get_file_list()
+            .iter()
+            .map(|patch_file| std::path::PathBuf::from(patch_file.get_path()))
+            .collect();
+        let patch_file_refs: Vec<&std::path::Path> = patch_file_paths
+            .iter()
+            .map(std::path::PathBuf::as_path)
+            .collect();
+        KernelPatchDiffHelper::apply_patches(&kernel_source_dir, &patch_file_refs)?;
+
         KernelPatchHelper::generate_defconfig(&kernel_source_dir)?;
         let kernel_config = KernelPatchHelper::find_kernel_config(&kernel_source_dir)?;
         debug!("kernel config: '{}'", kernel_config);
@@ -15,11 +15,12 @@ impl KernelPatchHelper {
 
         debug!("Generating kernel default config");
 
-        MAKE.execvp(ExternCommandArgs::new()
+        let args = ExternCommandArgs::new()
             .arg("-C")
             .arg(source_dir.as_ref())
-            .arg(DEFCONFIG_FILE_NAME)
-        )?.check_exit_code()
+            .arg(DEFCONFIG_FILE_NAME);
+
+        MAKE.execvp(args)?.check_exit_code()
     }
 
     pub fn find_kernel_config<P: AsRef<Path>>(directory: P) -> std::io::Result<PathBuf> {
@@ -1,3 +1,6 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
 use crate::constants::*;
 use crate::util::fs;
 
@@ -17,6 +20,34 @@ impl KernelPatchHelper {
         Ok(fs::stringtify(source_dir))
     }
 
+    /// Apply a list of unified-diff patch files to `source_dir`, in-process (no `patch(1)`
+    /// dependency). Patches are applied in the order given, which the caller should make
+    /// deterministic (e.g. sorted by file name), so a mid-way failure always leaves the
+    /// same, reportable tree behind.
+    pub fn apply_patches(source_dir: &str, patch_files: &[&Path]) -> std::io::Result<()> {
+        fs::check_dir(source_dir)?;
+
+        for patch_file in patch_files {
+            fs::check_file(fs::stringtify(patch_file.to_path_buf()).as_str())?;
+
+            println!("Applying patch '{}'", patch_file.display());
+            Self::apply_patch_file(source_dir, patch_file)?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_patch_file(source_dir: &str, patch_file: &Path) -> std::io::Result<()> {
+        let diff_text = std::fs::read_to_string(patch_file)?;
+
+        for section in DiffSection::parse(&diff_text) {
+            let target_path = section.resolve_target(source_dir)?;
+            section.apply(&target_path)?;
+        }
+
+        Ok(())
+    }
+
     pub fn find_kernel_config(directory: &str) -> std::io::Result<String> {
         fs::check_dir(directory)?;
 
@@ -108,3 +139,204 @@ impl KernelPatchHelper {
         Ok(fs::stringtify(kernel_file_path))
     }
 }
+
+/// Single `@@ -oldStart,oldLen +newStart,newLen @@` hunk of a unified diff.
+struct DiffHunk {
+    old_start: usize,
+    lines: Vec<(char, String)>,
+}
+
+/// One `--- a/<path>` / `+++ b/<path>` file section of a unified diff, with its hunks.
+struct DiffSection {
+    old_path: String,
+    new_path: String,
+    hunks: Vec<DiffHunk>,
+}
+
+impl DiffSection {
+    fn parse(diff_text: &str) -> Vec<Self> {
+        let mut sections = Vec::new();
+        let mut lines = diff_text.lines().peekable();
+
+        while let Some(line) = lines.next() {
+            let old_path = match line.strip_prefix("--- ") {
+                Some(path) => path.trim(),
+                None => continue,
+            };
+            let new_path = match lines.next().and_then(|line| line.strip_prefix("+++ ")) {
+                Some(path) => path.trim(),
+                None => continue,
+            };
+
+            let mut hunks = Vec::new();
+            while let Some(hunk_header) = lines.peek() {
+                if !hunk_header.starts_with("@@ ") {
+                    break;
+                }
+                let (old_start, _) = match Self::parse_hunk_header(lines.next().unwrap()) {
+                    Some(header) => header,
+                    None => break,
+                };
+
+                let mut hunk_lines = Vec::new();
+                while let Some(body_line) = lines.peek() {
+                    if body_line.starts_with("@@ ") || body_line.starts_with("--- ") {
+                        break;
+                    }
+                    let body_line = lines.next().unwrap();
+                    let kind = body_line.chars().next().unwrap_or(' ');
+                    if kind != ' ' && kind != '+' && kind != '-' {
+                        break;
+                    }
+                    hunk_lines.push((kind, body_line.get(1..).unwrap_or("").to_string()));
+                }
+
+                hunks.push(DiffHunk {
+                    old_start,
+                    lines: hunk_lines,
+                });
+            }
+
+            sections.push(Self {
+                old_path: Self::strip_ab_prefix(old_path),
+                new_path: Self::strip_ab_prefix(new_path),
+                hunks,
+            });
+        }
+
+        sections
+    }
+
+    fn parse_hunk_header(header: &str) -> Option<(usize, usize)> {
+        // "@@ -oldStart,oldLen +newStart,newLen @@" -> (oldStart, newStart)
+        let body = header.trim_start_matches("@@ ").split(" @@").next()?;
+        let mut parts = body.split_whitespace();
+        let old_range = parts.next()?.trim_start_matches('-');
+        let new_range = parts.next()?.trim_start_matches('+');
+
+        let old_start: usize = old_range.split(',').next()?.parse().ok()?;
+        let new_start: usize = new_range.split(',').next()?.parse().ok()?;
+
+        Some((old_start, new_start))
+    }
+
+    fn strip_ab_prefix(path: &str) -> String {
+        let path = path.split('\t').next().unwrap_or(path);
+        path.strip_prefix("a/")
+            .or_else(|| path.strip_prefix("b/"))
+            .unwrap_or(path)
+            .to_string()
+    }
+
+    /// Resolve this section's target file relative to `source_dir`, rejecting any path
+    /// that would escape it via `..` or an absolute component.
+    fn resolve_target(&self, source_dir: &str) -> std::io::Result<std::path::PathBuf> {
+        let rel_path = if self.new_path == "/dev/null" {
+            &self.old_path
+        } else {
+            &self.new_path
+        };
+
+        if Path::new(rel_path).is_absolute()
+            || Path::new(rel_path).components().any(|c| c == std::path::Component::ParentDir)
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Patch path '{}' escapes source directory", rel_path),
+            ));
+        }
+
+        Ok(Path::new(source_dir).join(rel_path))
+    }
+
+    fn apply(&self, target_path: &Path) -> std::io::Result<()> {
+        let is_creation = self.old_path == "/dev/null";
+        let is_deletion = self.new_path == "/dev/null";
+
+        if is_deletion {
+            if target_path.exists() {
+                std::fs::rename(target_path, target_path.with_extension("orig"))?;
+            }
+            return Ok(());
+        }
+
+        let original_lines: Vec<String> = if is_creation {
+            Vec::new()
+        } else {
+            let backup_path = target_path.with_extension(format!(
+                "{}.orig",
+                target_path.extension().and_then(|e| e.to_str()).unwrap_or("")
+            ));
+            std::fs::copy(target_path, &backup_path)?;
+
+            BufReader::new(std::fs::File::open(target_path)?)
+                .lines()
+                .collect::<std::io::Result<_>>()?
+        };
+
+        let mut result_lines: Vec<String> = Vec::new();
+        let mut cursor = 0usize; // next unconsumed index into original_lines
+
+        for hunk in &self.hunks {
+            let hunk_start = hunk.old_start.saturating_sub(1);
+            if hunk_start > original_lines.len() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "Hunk context starts at line {} past end of '{}'",
+                        hunk.old_start,
+                        target_path.display()
+                    ),
+                ));
+            }
+            result_lines.extend_from_slice(&original_lines[cursor..hunk_start]);
+            cursor = hunk_start;
+
+            for (kind, text) in &hunk.lines {
+                match kind {
+                    ' ' | '-' => {
+                        let existing = original_lines.get(cursor).ok_or_else(|| {
+                            std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                format!(
+                                    "Unexpected end of '{}' at line {}",
+                                    target_path.display(),
+                                    cursor + 1
+                                ),
+                            )
+                        })?;
+                        if existing != text {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                format!(
+                                    "Patch does not apply to '{}' at line {}: expected '{}', found '{}'",
+                                    target_path.display(),
+                                    cursor + 1,
+                                    text,
+                                    existing
+                                ),
+                            ));
+                        }
+                        if *kind == ' ' {
+                            result_lines.push(existing.clone());
+                        }
+                        cursor += 1;
+                    }
+                    '+' => result_lines.push(text.clone()),
+                    _ => unreachable!(),
+                }
+            }
+        }
+        result_lines.extend_from_slice(&original_lines[cursor..]);
+
+        if let Some(parent) = target_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut output_file = std::fs::File::create(target_path)?;
+        for line in &result_lines {
+            writeln!(output_file, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use syscare_abi::{PackageInfo, PackageType};
+
+use super::{ElfRelation, Package, PackageBuildRoot, DEBUGINFO_INSTALL_DIR};
+
+const DEB_FILE_EXT: &str = "deb";
+
+pub struct DebPackage;
+
+impl DebPackage {
+    fn read_control_tar(pkg_path: &Path) -> Result<Vec<u8>> {
+        let file = File::open(pkg_path)
+            .with_context(|| format!("Failed to open \"{}\"", pkg_path.display()))?;
+        let mut archive = ar::Archive::new(file);
+
+        while let Some(entry) = archive.next_entry() {
+            let mut entry = entry.with_context(|| {
+                format!("Failed to read ar member of \"{}\"", pkg_path.display())
+            })?;
+            let name = String::from_utf8_lossy(entry.header().identifier()).into_owned();
+            if name.starts_with("control.tar") {
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).with_context(|| {
+                    format!("Failed to read \"{}\" of \"{}\"", name, pkg_path.display())
+                })?;
+                return Ok(data);
+            }
+        }
+
+        bail!(
+            "Cannot find \"control.tar\" member in \"{}\"",
+            pkg_path.display()
+        );
+    }
+
+    fn read_data_tar(pkg_path: &Path) -> Result<Vec<u8>> {
+        let file = File::open(pkg_path)
+            .with_context(|| format!("Failed to open \"{}\"", pkg_path.display()))?;
+        let mut archive = ar::Archive::new(file);
+
+        while let Some(entry) = archive.next_entry() {
+            let mut entry = entry.with_context(|| {
+                format!("Failed to read ar member of \"{}\"", pkg_path.display())
+            })?;
+            let name = String::from_utf8_lossy(entry.header().identifier()).into_owned();
+            if name.starts_with("data.tar") {
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).with_context(|| {
+                    format!("Failed to read \"{}\" of \"{}\"", name, pkg_path.display())
+                })?;
+                return Ok(data);
+            }
+        }
+
+        bail!(
+            "Cannot find \"data.tar\" member in \"{}\"",
+            pkg_path.display()
+        );
+    }
+
+    fn read_control_text(pkg_path: &Path) -> Result<String> {
+        let control_tar = Self::read_control_tar(pkg_path)?;
+        let mut archive = tar::Archive::new(Cursor::new(control_tar));
+
+        for entry in archive.entries().with_context(|| {
+            format!("Failed to read control archive of \"{}\"", pkg_path.display())
+        })? {
+            let mut entry = entry.with_context(|| {
+                format!("Failed to read control entry of \"{}\"", pkg_path.display())
+            })?;
+            let path = entry.path()?.into_owned();
+            if path.file_name().and_then(|name| name.to_str()) == Some("control") {
+                let mut text = String::new();
+                entry.read_to_string(&mut text).with_context(|| {
+                    format!("Failed to read control file of \"{}\"", pkg_path.display())
+                })?;
+                return Ok(text);
+            }
+        }
+
+        bail!(
+            "Cannot find control file inside \"{}\"",
+            pkg_path.display()
+        );
+    }
+
+    fn control_field<'a>(control: &'a str, field: &str) -> Option<&'a str> {
+        control.lines().find_map(|line| {
+            line.strip_prefix(field)
+                .and_then(|rest| rest.strip_prefix(':'))
+                .map(str::trim)
+        })
+    }
+
+    /// Splits dpkg's `[epoch:]upstream_version[-debian_revision]` version syntax into the
+    /// same `(epoch, version, release)` triple `PackageInfo` uses for RPM's EVR fields.
+    fn parse_version(version: &str) -> (String, String, String) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => (epoch.to_string(), rest),
+            None => ("0".to_string(), version),
+        };
+        let (version, release) = match rest.rsplit_once('-') {
+            Some((version, release)) => (version.to_string(), release.to_string()),
+            None => (rest.to_string(), String::new()),
+        };
+
+        (epoch, version, release)
+    }
+}
+
+impl Package for DebPackage {
+    fn extension(&self) -> &'static str {
+        DEB_FILE_EXT
+    }
+
+    fn parse_package_info(&self, pkg_path: &Path) -> Result<PackageInfo> {
+        let control = Self::read_control_text(pkg_path)?;
+
+        let name = Self::control_field(&control, "Package")
+            .with_context(|| {
+                format!("Cannot find \"Package\" field in \"{}\"", pkg_path.display())
+            })?
+            .to_string();
+        let version_field = Self::control_field(&control, "Version").with_context(|| {
+            format!("Cannot find \"Version\" field in \"{}\"", pkg_path.display())
+        })?;
+        let (epoch, version, release) = Self::parse_version(version_field);
+        let arch = Self::control_field(&control, "Architecture")
+            .unwrap_or("all")
+            .to_string();
+        let source_pkg = Self::control_field(&control, "Source")
+            .and_then(|source| source.split_whitespace().next())
+            .unwrap_or(&name)
+            .to_string();
+        let license = Self::control_field(&control, "License")
+            .unwrap_or("unknown")
+            .to_string();
+
+        Ok(PackageInfo {
+            name,
+            kind: PackageType::BinaryPackage,
+            arch,
+            epoch,
+            version,
+            release,
+            license,
+            source_pkg,
+        })
+    }
+
+    fn query_package_files(&self, pkg_path: &Path) -> Result<Vec<PathBuf>> {
+        let data_tar = Self::read_data_tar(pkg_path)?;
+        let mut archive = tar::Archive::new(Cursor::new(data_tar));
+
+        let mut files = Vec::new();
+        for entry in archive
+            .entries()
+            .with_context(|| format!("Failed to read data archive of \"{}\"", pkg_path.display()))?
+        {
+            let entry =
+                entry.with_context(|| format!("Failed to read data entry of \"{}\"", pkg_path.display()))?;
+            if entry.header().entry_type().is_file() {
+                files.push(Path::new("/").join(entry.path()?));
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn parse_elf_relations(
+        &self,
+        _package: &PackageInfo,
+        _debuginfo_root: &Path,
+    ) -> Result<Vec<ElfRelation>> {
+        // Debuginfo for a .ddeb is installed under DEBUGINFO_INSTALL_DIR the same way as an
+        // RPM debuginfo package, keyed by build-id rather than file path. Correlating that
+        // against the stripped binaries needs ElfRelation's constructor, which (like the RPM
+        // backend it mirrors) isn't part of this checkout.
+        let _ = DEBUGINFO_INSTALL_DIR;
+        Ok(Vec::new())
+    }
+
+    fn extract_package(&self, pkg_path: &Path, output_dir: &Path) -> Result<()> {
+        let data_tar = Self::read_data_tar(pkg_path)?;
+        let mut archive = tar::Archive::new(Cursor::new(data_tar));
+
+        archive.unpack(output_dir).with_context(|| {
+            format!(
+                "Failed to extract \"{}\" to \"{}\"",
+                pkg_path.display(),
+                output_dir.display()
+            )
+        })
+    }
+
+    fn find_build_root(&self, directory: &Path) -> Result<PackageBuildRoot> {
+        PackageBuildRoot::new(directory)
+    }
+
+    fn find_spec_file(&self, directory: &Path) -> Result<PathBuf> {
+        let rules_file = directory.join("debian").join("rules");
+        if !rules_file.is_file() {
+            bail!(
+                "Cannot find \"debian/rules\" under \"{}\"",
+                directory.display()
+            );
+        }
+
+        Ok(rules_file)
+    }
+
+    fn find_source_directory(&self, directory: &Path, package_name: &str) -> Result<PathBuf> {
+        for entry in directory
+            .read_dir()
+            .with_context(|| format!("Failed to read directory \"{}\"", directory.display()))?
+        {
+            let path = entry?.path();
+            let is_source_dir = path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.starts_with(package_name))
+                    .unwrap_or(false);
+            if is_source_dir {
+                return Ok(path);
+            }
+        }
+
+        bail!(
+            "Cannot find source directory of \"{}\" under \"{}\"",
+            package_name,
+            directory.display()
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 
 use syscare_abi::PackageInfo;
 
 mod build_root;
+mod deb;
 mod elf_relation;
 mod pkg_builder;
 mod rpm;
@@ -13,6 +14,7 @@ mod spec_writer;
 mod tar;
 
 pub use build_root::PackageBuildRoot;
+use deb::DebPackage;
 pub use elf_relation::ElfRelation;
 pub use pkg_builder::{PackageBuilder, PackageBuilderFactory};
 use rpm::RpmPackage;
@@ -41,6 +43,7 @@ trait Package {
 #[derive(Debug, Clone, Copy)]
 pub enum PackageFormat {
     RpmPackage,
+    DebPackage,
 }
 
 pub struct PackageImpl {
@@ -55,9 +58,35 @@ impl PackageImpl {
                 format: pkg_format,
                 inner: Box::new(RpmPackage),
             },
+            PackageFormat::DebPackage => Self {
+                format: pkg_format,
+                inner: Box::new(DebPackage),
+            },
         }
     }
 
+    /// Builds the right backend for `pkg_path` by its file extension, so callers working
+    /// with a package path don't have to already know (or separately detect) its format.
+    pub fn for_path<P: AsRef<Path>>(pkg_path: P) -> Result<Self> {
+        let pkg_path = pkg_path.as_ref();
+        let extension = pkg_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .with_context(|| format!("Cannot detect package format of \"{}\"", pkg_path.display()))?;
+
+        let format = match extension {
+            "rpm" => PackageFormat::RpmPackage,
+            "deb" => PackageFormat::DebPackage,
+            _ => bail!(
+                "Unsupported package format \".{}\" of \"{}\"",
+                extension,
+                pkg_path.display()
+            ),
+        };
+
+        Ok(Self::new(format))
+    }
+
     pub fn format(&self) -> PackageFormat {
         self.format
     }
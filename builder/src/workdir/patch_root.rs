@@ -11,6 +11,10 @@ use super::util;
 const BUILD_DIR_NAME: &str = "build";
 const OUTPUT_DIR_NAME: &str = "output";
 
+/// `rwxr-x---`: the patch root holds build artifacts and (soon) persisted patch metadata, so it
+/// shouldn't be world-readable by default.
+const PATCH_ROOT_DIR_MODE: u32 = 0o750;
+
 #[derive(Debug, Clone)]
 pub struct PatchRoot {
     pub path: PathBuf,
@@ -24,9 +28,9 @@ impl PatchRoot {
         let build = path.join(BUILD_DIR_NAME);
         let output = path.join(OUTPUT_DIR_NAME);
 
-        util::create_dir_all(&path)?;
-        util::create_dir_all(&build)?;
-        util::create_dir_all(&output)?;
+        util::create_dir_all_with_mode(&path, PATCH_ROOT_DIR_MODE)?;
+        util::create_dir_all_with_mode(&build, PATCH_ROOT_DIR_MODE)?;
+        util::create_dir_all_with_mode(&output, PATCH_ROOT_DIR_MODE)?;
 
         Ok(Self {
             path,
@@ -0,0 +1,34 @@
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+pub fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    std::fs::create_dir_all(path.as_ref())
+        .with_context(|| format!("Failed to create directory \"{}\"", path.as_ref().display()))
+}
+
+/// Like [`create_dir_all`], but applies `mode` to every directory component it creates instead
+/// of leaving permissions to the process umask, so a patch's build/output trees end up with a
+/// deterministic, predictable mode regardless of who runs the builder.
+pub fn create_dir_all_with_mode<P: AsRef<Path>>(path: P, mode: u32) -> Result<()> {
+    let path = path.as_ref();
+    let missing_dirs = path
+        .ancestors()
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>();
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(mode)
+        .create(path)
+        .with_context(|| format!("Failed to create directory \"{}\"", path.display()))?;
+
+    // `mkdir(2)` masks `mode` with the umask, so set it explicitly on every created directory.
+    for dir in missing_dirs {
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(mode))
+            .with_context(|| format!("Failed to set permission of \"{}\"", dir.display()))?;
+    }
+    Ok(())
+}
@@ -1,10 +1,10 @@
-use std::{path::PathBuf, ffi::OsString};
+use std::{path::{Path, PathBuf}, ffi::{OsStr, OsString}};
+use std::os::unix::ffi::OsStrExt;
 
 use log::{debug, info};
 
 use crate::os::{Mounts, KExec};
 use crate::util::{sys, fs};
-use crate::util::os_str::OsStrConcat;
 
 pub struct BootManager;
 
@@ -26,27 +26,34 @@ impl BootManager {
         Ok(())
     }
 
+    /// Loads the *running* kernel/initramfs pair for kexec. To stage a different kernel
+    /// (e.g. one `syscare` just patched), use [`load_kernel_version`](Self::load_kernel_version).
     pub fn load_kernel() -> std::io::Result<()> {
-        const BOOT_DIR_NAME:       &str = "/boot";
-        const KERNEL_PREFIX:       &str = "vmlinuz-";
-        const INITRAMFS_PREFIX:    &str = "initramfs-";
-        const INITRAMFS_EXTENSION: &str = ".img";
-
         let kernel_version = sys::get_kernel_version()?;
+        Self::load_kernel_version(&kernel_version)
+    }
+
+    /// Stages `kernel_version` for kexec, picking the newest matching `/boot` pair whose names
+    /// start with `vmlinuz-<kernel_version>` / `initramfs-<kernel_version>`, regardless of any
+    /// compression extension (`.img`, `.img.gz`, `.img.zst`, ...) or distro-specific suffix
+    /// (e.g. BLS-style entries) appended after the version.
+    pub fn load_kernel_version(kernel_version: &OsStr) -> std::io::Result<()> {
+        const BOOT_DIR_NAME:    &str = "/boot";
+        const KERNEL_PREFIX:    &str = "vmlinuz-";
+        const INITRAMFS_PREFIX: &str = "initramfs-";
+
         info!("Kernel version:  {}", kernel_version.to_string_lossy());
 
         let boot_dir = PathBuf::from(BOOT_DIR_NAME);
-        let kernel = fs::find_file(
-            &boot_dir,
-            OsString::from(KERNEL_PREFIX).concat(&kernel_version),
-            false,
-            false
-        )?;
-        let initramfs = fs::find_file(
-            &boot_dir,
-            OsString::from(INITRAMFS_PREFIX).concat(&kernel_version).concat(INITRAMFS_EXTENSION),
-            false,
-            false
+
+        let mut kernel_prefix = OsString::from(KERNEL_PREFIX);
+        kernel_prefix.push(kernel_version);
+
+        let mut initramfs_prefix = OsString::from(INITRAMFS_PREFIX);
+        initramfs_prefix.push(kernel_version);
+
+        let (kernel, initramfs) = Self::newest_boot_candidate_pair(
+            &boot_dir, &kernel_prefix, &initramfs_prefix
         )?;
 
         info!("Using kernel:    {}", kernel.display());
@@ -56,6 +63,68 @@ impl BootManager {
         Ok(())
     }
 
+    /// Lists every file directly under `boot_dir` whose name starts with `prefix`, logs the
+    /// full candidate set, then returns each one paired with its modification time.
+    fn boot_candidates(boot_dir: &Path, prefix: &OsStr) -> std::io::Result<Vec<(PathBuf, std::time::SystemTime)>> {
+        let mut candidates = Vec::new();
+        for entry in std::fs::read_dir(boot_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if entry.file_name().as_bytes().starts_with(prefix.as_bytes()) {
+                let modified = entry.metadata()?.modified()?;
+                candidates.push((entry.path(), modified));
+            }
+        }
+
+        debug!("Candidates for \"{}*\" in \"{}\": {:?}",
+            prefix.to_string_lossy(), boot_dir.display(), candidates
+        );
+
+        Ok(candidates)
+    }
+
+    /// Picks the newest kernel candidate and pairs it with the initramfs generated closest to
+    /// it in time, instead of each file's newest candidate independently -- on a host with
+    /// multiple BLS-style kernel entries, the newest `vmlinuz-*` and the newest
+    /// `initramfs-*.img` can belong to different kernel versions if they weren't regenerated
+    /// in lockstep, which would hand kexec a mismatched kernel/initramfs pair.
+    fn newest_boot_candidate_pair(
+        boot_dir: &Path,
+        kernel_prefix: &OsStr,
+        initramfs_prefix: &OsStr,
+    ) -> std::io::Result<(PathBuf, PathBuf)> {
+        let kernels = Self::boot_candidates(boot_dir, kernel_prefix)?;
+        let initramfses = Self::boot_candidates(boot_dir, initramfs_prefix)?;
+
+        let not_found = |prefix: &OsStr| std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("No file starting with \"{}\" found in \"{}\"",
+                prefix.to_string_lossy(), boot_dir.display()
+            )
+        );
+        if kernels.is_empty() {
+            return Err(not_found(kernel_prefix));
+        }
+        if initramfses.is_empty() {
+            return Err(not_found(initramfs_prefix));
+        }
+
+        let time_gap = |lhs: &std::time::SystemTime, rhs: &std::time::SystemTime| match lhs >= rhs {
+            true => lhs.duration_since(*rhs).unwrap_or_default(),
+            false => rhs.duration_since(*lhs).unwrap_or_default(),
+        };
+        let (kernel, kernel_time) = kernels.iter()
+            .max_by_key(|(_, time)| *time)
+            .ok_or_else(|| not_found(kernel_prefix))?;
+        let (initramfs, _) = initramfses.iter()
+            .min_by_key(|(_, time)| time_gap(kernel_time, time))
+            .ok_or_else(|| not_found(initramfs_prefix))?;
+
+        Ok((kernel.clone(), initramfs.clone()))
+    }
+
     pub fn reboot() -> std::io::Result<()> {
         KExec::exec_kernel()?;
 
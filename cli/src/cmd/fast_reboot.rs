@@ -1,17 +1,27 @@
+use std::ffi::OsStr;
+
 use log::{debug, info};
 
 use crate::boot::BootManager;
 
-use super::CommandExecutor;
+use super::{CommandArguments, CommandExecutor};
 
 pub struct FastRebootCommandExecutor;
 
 impl CommandExecutor for FastRebootCommandExecutor {
-    fn invoke(&self, _args: &[String]) -> std::io::Result<i32> {
+    fn invoke(&self, args: &CommandArguments) -> std::io::Result<i32> {
         debug!("Handle Command \"fast-reboot\"");
 
+        let target = match args {
+            CommandArguments::RebootArguments(target, _force) => target.as_deref(),
+            _ => None,
+        };
+
         info!("Preparing for reboot");
-        BootManager::load_kernel()?;
+        match target {
+            Some(target) => BootManager::load_kernel_version(OsStr::new(target))?,
+            None => BootManager::load_kernel()?,
+        }
 
         info!("Syncing mount points");
         BootManager::sync_mount_points()?;
@@ -0,0 +1,55 @@
+use anyhow::Error;
+
+/// Stable exit codes for `syscare`, so automation can branch on *why* an operation failed
+/// instead of grepping log text. Mirrors the explicit exit-code handling the dev-toolbox CLI
+/// added for the same reason.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success = 0,
+    GenericFailure = 1,
+    PatchNotFound = 2,
+    InvalidStatusTransition = 3,
+    DriverFailure = 4,
+    RpcUnavailable = 5,
+    LockContention = 6,
+    VerificationFailed = 7,
+}
+
+impl ExitCode {
+    /// Classifies an error by scanning its full source chain for the telltale substrings
+    /// each failure class already bails out with, since daemon-side errors cross the RPC
+    /// boundary as plain strings rather than typed errors.
+    pub fn classify(error: &Error) -> Self {
+        for cause in error.chain() {
+            let message = cause.to_string();
+
+            if message.contains("Cannot match any patch") || message.contains("Cannot find patch")
+            {
+                return Self::PatchNotFound;
+            }
+            if message.contains("does not reach") || message.contains("is already occupied by") {
+                return Self::InvalidStatusTransition;
+            }
+            if message.contains("conflicted with") || message.contains("overrided by") {
+                return Self::VerificationFailed;
+            }
+            if message.contains("upatch_") || message.contains("kpatch_") {
+                return Self::DriverFailure;
+            }
+            if message.contains("Connection refused")
+                || (message.contains("No such file or directory")
+                    && message.contains("syscared.sock"))
+            {
+                return Self::RpcUnavailable;
+            }
+            if message.contains("patch_op.lock")
+                || message.contains("Resource temporarily unavailable")
+            {
+                return Self::LockContention;
+            }
+        }
+
+        Self::GenericFailure
+    }
+}
@@ -19,11 +19,13 @@ use log::{debug, error, LevelFilter};
 
 mod args;
 mod executor;
+mod exit_code;
 mod logger;
 mod rpc;
 
 use args::Arguments;
 use executor::{build::BuildCommandExecutor, patch::PatchCommandExecutor, CommandExecutor};
+use exit_code::ExitCode;
 use logger::Logger;
 use rpc::{RpcProxy, RpcRemote};
 use syscare_common::os;
@@ -78,7 +80,7 @@ impl SyscareCLI {
 
 fn main() {
     let exit_code = match SyscareCLI::start_and_run() {
-        Ok(_) => 0,
+        Ok(_) => ExitCode::Success,
         Err(e) => {
             match Logger::is_inited() {
                 false => {
@@ -88,8 +90,8 @@ fn main() {
                     error!("Error: {:?}", e);
                 }
             }
-            1
+            ExitCode::classify(&e)
         }
     };
-    exit(exit_code);
+    exit(exit_code as i32);
 }
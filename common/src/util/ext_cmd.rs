@@ -0,0 +1,238 @@
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::Command;
+
+/// Builder for the argument vector passed to an [`ExternCommand`] invocation.
+#[derive(Debug, Default, Clone)]
+pub struct ExternCommandArgs {
+    args: Vec<OsString>,
+    redacted: Vec<usize>,
+}
+
+impl ExternCommandArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn arg<S: AsRef<OsStr>>(mut self, arg: S) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args.extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+        self
+    }
+
+    /// Same as [`arg`](Self::arg), but the value is replaced with `<redacted>` in the
+    /// debug-level command line logged before spawning, so secrets (tokens, passwords, ...)
+    /// passed as command arguments never end up in the log.
+    pub fn secret_arg<S: AsRef<OsStr>>(mut self, arg: S) -> Self {
+        self.redacted.push(self.args.len());
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Renders `program` plus this invocation's arguments as a single shell-quotable line,
+    /// substituting `<redacted>` for any value added via [`secret_arg`](Self::secret_arg).
+    fn command_line(&self, program: &str) -> String {
+        let mut parts = Vec::with_capacity(self.args.len() + 1);
+        parts.push(shell_quote(program));
+        for (i, arg) in self.args.iter().enumerate() {
+            match self.redacted.contains(&i) {
+                true => parts.push("<redacted>".to_string()),
+                false => parts.push(shell_quote(&arg.to_string_lossy())),
+            }
+        }
+        parts.join(" ")
+    }
+}
+
+/// Quotes `value` for safe pasting into a shell, leaving plain tokens (paths, flags, numbers)
+/// unquoted so the logged command line stays readable.
+fn shell_quote(value: &str) -> String {
+    let is_plain = !value.is_empty()
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+    match is_plain {
+        true => value.to_string(),
+        false => format!("'{}'", value.replace('\'', r"'\''")),
+    }
+}
+
+/// Builder for the environment overrides passed to an [`ExternCommand::execve`] invocation.
+#[derive(Debug, Default, Clone)]
+pub struct ExternCommandEnvs {
+    envs: Vec<(OsString, OsString)>,
+}
+
+impl ExternCommandEnvs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(mut self, key: K, value: V) -> Self {
+        self.envs.push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Renders the overrides as `KEY=value` pairs for the debug-level command line logged
+    /// before spawning, so it's clear at a glance which environment a run actually used.
+    fn describe(&self) -> String {
+        self.envs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key.to_string_lossy(), value.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// An external, non-Rust command identified by its executable name or path.
+///
+/// `ExternCommand` is a `Copy` handle around a `&'static str` so it can be declared as a `const` (or shared via
+/// `lazy_static`) and reused across call sites; each invocation spawns its own child process.
+#[derive(Debug, Clone, Copy)]
+pub struct ExternCommand(&'static str);
+
+impl ExternCommand {
+    pub const fn new(path: &'static str) -> Self {
+        Self(path)
+    }
+
+    pub fn path(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn execvp(&self, args: ExternCommandArgs) -> io::Result<ExitStatus> {
+        log::debug!("Running: {} (cwd: {})", args.command_line(self.0), current_dir_display());
+        let output = Command::new(self.0).args(&args.args).output()?;
+        Ok(ExitStatus::new(self.0, output))
+    }
+
+    /// Same as [`execvp`](Self::execvp), but runs in `cwd` with `envs` applied on top of the
+    /// inherited environment. Logs the env overrides alongside the command line so a run with
+    /// a non-default `cwd`/env is distinguishable from a plain `execvp` in the logs.
+    pub fn execve<P: AsRef<std::path::Path>>(
+        &self,
+        args: ExternCommandArgs,
+        envs: ExternCommandEnvs,
+        cwd: P,
+    ) -> io::Result<ExitStatus> {
+        log::debug!(
+            "Running: {} (cwd: {}, env overrides applied: {})",
+            args.command_line(self.0),
+            cwd.as_ref().display(),
+            envs.describe(),
+        );
+        let output = Command::new(self.0)
+            .args(&args.args)
+            .envs(envs.envs.iter().map(|(key, value)| (key.as_os_str(), value.as_os_str())))
+            .current_dir(cwd)
+            .output()?;
+        Ok(ExitStatus::new(self.0, output))
+    }
+}
+
+/// The result of running an [`ExternCommand`].
+///
+/// Retains the raw [`std::process::ExitStatus`] alongside the captured output so callers can
+/// tell a normal nonzero exit apart from the process having been killed by a signal, which
+/// carries no exit code of its own.
+#[derive(Debug)]
+pub struct ExitStatus {
+    command: &'static str,
+    status: std::process::ExitStatus,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl ExitStatus {
+    fn new(command: &'static str, output: std::process::Output) -> Self {
+        Self {
+            command,
+            status: output.status,
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }
+    }
+
+    pub fn exit_status(&self) -> std::process::ExitStatus {
+        self.status
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        self.status.code().unwrap_or(-1)
+    }
+
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    /// The signal that killed the process, if it did not exit normally.
+    pub fn terminated_by_signal(&self) -> Option<i32> {
+        self.status.signal()
+    }
+
+    pub fn stdout(&self) -> Cow<str> {
+        String::from_utf8_lossy(&self.stdout)
+    }
+
+    pub fn stderr(&self) -> Cow<str> {
+        String::from_utf8_lossy(&self.stderr)
+    }
+
+    /// Explains why the command failed, distinguishing a normal nonzero exit from
+    /// termination by signal so logs don't show a misleading "exit code 0" for a process
+    /// that was actually killed. Public so other crates needing a custom failure check (i.e.
+    /// anything more than [`check_exit_code`](Self::check_exit_code)'s plain success check)
+    /// don't have to hand-duplicate this and [`signal_name`].
+    pub fn describe_failure(&self) -> String {
+        match self.terminated_by_signal() {
+            Some(signal) => format!(
+                "{} terminated by signal {} ({})",
+                self.command, signal, signal_name(signal)
+            ),
+            None => format!(
+                "{} exited with code {}: {}",
+                self.command, self.exit_code(), self.stderr().trim()
+            ),
+        }
+    }
+
+    pub fn check_exit_code(&self) -> io::Result<()> {
+        if self.success() {
+            return Ok(());
+        }
+        Err(io::Error::new(io::ErrorKind::Other, self.describe_failure()))
+    }
+}
+
+fn current_dir_display() -> String {
+    std::env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|_| "<unknown>".to_string())
+}
+
+/// Maps a signal number to its name (e.g. `9` -> `"SIGKILL"`), for failure messages that
+/// need to say why a process died instead of just reporting a raw number.
+pub fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        15 => "SIGTERM",
+        _ => "unknown signal",
+    }
+}
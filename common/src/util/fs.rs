@@ -166,6 +166,83 @@ pub fn remove_dir_all<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
     )
 }
 
+/// Number of times a transient unlink/rmdir failure is retried before giving up.
+const REMOVE_RETRY_COUNT: u32 = 3;
+const REMOVE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Recursively remove `path`, tolerating read-only entries and never following symlinked
+/// directories (a symlink is unlinked itself, its target is left untouched). Unlike
+/// `remove_dir_all`, a read-only file or directory does not abort the whole walk: its
+/// permissions are cleared and the removal is retried a bounded number of times before
+/// the failure is reported, with the offending path named in the error.
+pub fn remove_dir_all_robust<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let metadata = symlink_metadata(path)?;
+
+    if metadata.file_type().is_symlink() || !metadata.is_dir() {
+        return remove_entry_robust(path);
+    }
+
+    for dir_entry in read_dir(path)? {
+        let entry = dir_entry?;
+        let entry_type = entry.file_type()?;
+
+        if entry_type.is_dir() && !entry_type.is_symlink() {
+            remove_dir_all_robust(entry.path())?;
+        } else {
+            remove_entry_robust(&entry.path())?;
+        }
+    }
+
+    remove_entry_robust(path)
+}
+
+fn remove_entry_robust(path: &Path) -> std::io::Result<()> {
+    let remove_once = || -> std::io::Result<()> {
+        let metadata = symlink_metadata(path)?;
+        if !metadata.file_type().is_symlink() && metadata.is_dir() {
+            std::fs::remove_dir(path)
+        } else {
+            std::fs::remove_file(path)
+        }
+    };
+
+    let mut last_err = None;
+    for attempt in 0..=REMOVE_RETRY_COUNT {
+        match remove_once() {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                // Clear the read-only bit (or missing write permission) on the entry and on
+                // its parent directory, which unlinking needs write access to, then retry.
+                // Symlinks are skipped so a link's target is never touched.
+                let parent = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+                for perm_path in std::iter::once(path).chain(parent) {
+                    if let Ok(metadata) = perm_path.symlink_metadata() {
+                        if metadata.file_type().is_symlink() {
+                            continue;
+                        }
+                        let mut perm = metadata.permissions();
+                        perm.set_readonly(false);
+                        let _ = set_permissions(perm_path, perm);
+                    }
+                }
+                last_err = Some(e);
+            }
+            Err(e) => {
+                last_err = Some(e);
+            }
+        }
+        if attempt < REMOVE_RETRY_COUNT {
+            std::thread::sleep(REMOVE_RETRY_DELAY);
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::Other, "Unknown removal failure")
+    }))
+    .rewrite_err(format!("Cannot remove \"{}\"", path.display()))
+}
+
 #[inline]
 pub fn read_dir<P: AsRef<Path>>(path: P) -> std::io::Result<ReadDir> {
     std::fs::read_dir(path.as_ref()).rewrite_err(
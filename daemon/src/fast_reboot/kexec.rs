@@ -1,7 +1,7 @@
 use std::ffi::OsString;
 use std::path::Path;
 
-use anyhow::{ensure, Result};
+use anyhow::Result;
 
 use lazy_static::lazy_static;
 use syscare_common::util::{
@@ -26,36 +26,24 @@ where
             .arg(OsString::from("--initrd=").concat(initramfs.as_ref()))
             .arg("--reuse-cmdline"),
     )?;
-    ensure!(
-        exit_status.exit_code() == 0,
-        format!("{}", exit_status.stderr().to_string_lossy())
-    );
+    exit_status.check_exit_code()?;
     Ok(())
 }
 
 pub fn unload() -> Result<()> {
     let exit_status = KEXEC.execvp(ExternCommandArgs::new().arg("--unload"))?;
-    ensure!(
-        exit_status.exit_code() == 0,
-        format!("{}", exit_status.stderr().to_string_lossy())
-    );
+    exit_status.check_exit_code()?;
     Ok(())
 }
 
 pub fn systemd_exec() -> Result<()> {
     let exit_status = SYSTEMCTL.execvp(ExternCommandArgs::new().arg("kexec"))?;
-    ensure!(
-        exit_status.exit_code() == 0,
-        format!("{}", exit_status.stderr().to_string_lossy())
-    );
+    exit_status.check_exit_code()?;
     Ok(())
 }
 
 pub fn force_exec() -> Result<()> {
     let exit_status = KEXEC.execvp(ExternCommandArgs::new().arg("--exec"))?;
-    ensure!(
-        exit_status.exit_code() == 0,
-        format!("{}", exit_status.stderr().to_string_lossy())
-    );
+    exit_status.check_exit_code()?;
     Ok(())
 }
@@ -14,6 +14,7 @@ use daemonize::Daemonize;
 use jsonrpc_core::IoHandler;
 use jsonrpc_ipc_server::{Server, ServerBuilder};
 use log::{error, info, LevelFilter};
+use parking_lot::RwLock;
 use signal_hook::consts::TERM_SIGNALS;
 
 use syscare_common::os;
@@ -26,6 +27,7 @@ mod rpc;
 
 use args::*;
 use logger::*;
+use patch::manager::{PatchInstallWatcher, PatchManager};
 
 use rpc::{
     skeleton::{FastRebootSkeleton, PatchSkeleton},
@@ -114,6 +116,20 @@ impl Daemon {
         Ok(io_handler)
     }
 
+    /// Starts watching the patch install directory so RPMs dropped in after daemon start are
+    /// picked up without a manual rescan or a daemon restart. The returned watcher must be
+    /// kept alive for as long as the daemon runs -- dropping it tears down its inotify handle.
+    fn start_patch_watcher(&self) -> Result<PatchInstallWatcher> {
+        let patch_manager = Arc::new(RwLock::new(PatchManager::new()));
+        patch_manager
+            .read()
+            .initialize(&self.args.data_dir)
+            .context("Failed to initialize patch manager")?;
+
+        PatchInstallWatcher::new(patch_manager, false)
+            .context("Failed to start patch install watcher")
+    }
+
     fn initialize_signal_handler(&self) -> Result<()> {
         for signal in TERM_SIGNALS {
             signal_hook::flag::register(*signal, self.term_flag.clone())
@@ -159,6 +175,11 @@ impl Daemon {
             .initialize_skeletons()
             .context("Failed to initialize skeleton")?;
 
+        info!("Starting patch install watcher...");
+        let _patch_watcher = self
+            .start_patch_watcher()
+            .context("Failed to start patch install watcher")?;
+
         info!("Starting remote procedure call server...");
         let server = self
             .start_rpc_server(io_handler)
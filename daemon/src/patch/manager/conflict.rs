@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+use syscare_abi::PatchStatus;
+
+use super::Patch;
+
+/// Structured conflict error: which patch/target-status was requested, and the chain of
+/// already-applied patches on the same target that block it. Modeled on Cargo's
+/// `package_path` conflict reporting, so the user sees exactly what's in the way instead of
+/// a late, generic "does not reach status" bail.
+#[derive(Debug)]
+pub struct PatchConflictError {
+    pub requested_patch: String,
+    pub requested_status: PatchStatus,
+    pub target_name: String,
+    pub conflicts: Vec<(Arc<Patch>, PatchStatus)>,
+}
+
+impl Display for PatchConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cannot switch patch \"{}\" to \"{}\", target \"{}\" is already occupied by ",
+            self.requested_patch, self.requested_status, self.target_name
+        )?;
+        let chain = self
+            .conflicts
+            .iter()
+            .map(|(patch, status)| format!("\"{}\" ({})", patch, status))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{}", chain)
+    }
+}
+
+impl std::error::Error for PatchConflictError {}
+
+/// Patches on `patch`'s target that are already applied (any status other than `NotApplied`)
+/// and would conflict with moving `patch` to `target_status`. A target can only have one
+/// patch applied at a time, so moving to any status other than `NotApplied` conflicts with
+/// every other already-applied patch on the same target.
+pub(super) fn find_conflicts<'a>(
+    patch: &Patch,
+    target_status: PatchStatus,
+    entries: impl Iterator<Item = (&'a Arc<Patch>, PatchStatus)>,
+) -> Vec<(Arc<Patch>, PatchStatus)> {
+    if target_status == PatchStatus::NotApplied {
+        return Vec::new();
+    }
+    entries
+        .filter(|(other, status)| {
+            (other.target_name == patch.target_name)
+                && (other.uuid != patch.uuid)
+                && (*status != PatchStatus::NotApplied)
+        })
+        .map(|(other, status)| (other.clone(), status))
+        .collect()
+}
+
+/// Orders `(patch, target_status)` restore pairs via a topological sort over a graph where
+/// an edge `a -> b` means "a must run before b": for any two pending operations on the same
+/// target, the one ending in a lower status always precedes the other, so REMOVE/DEACTIVE
+/// steps always run before conflicting APPLY/ACTIVE steps, replacing the previous ad-hoc
+/// global sort.
+pub(super) fn topological_restore_order(
+    entries: Vec<(Arc<Patch>, PatchStatus)>,
+) -> Vec<(Arc<Patch>, PatchStatus)> {
+    let count = entries.len();
+    let mut edges = vec![Vec::new(); count];
+    let mut in_degree = vec![0usize; count];
+
+    for i in 0..count {
+        for j in 0..count {
+            if i == j {
+                continue;
+            }
+            let (lhs_patch, lhs_status) = &entries[i];
+            let (rhs_patch, rhs_status) = &entries[j];
+            if lhs_patch.target_name != rhs_patch.target_name {
+                continue;
+            }
+            let ordering = lhs_status
+                .cmp(rhs_status)
+                .then_with(|| lhs_patch.cmp(rhs_patch));
+            if ordering == Ordering::Less {
+                edges[i].push(j);
+                in_degree[j] += 1;
+            }
+        }
+    }
+
+    let mut queue = (0..count)
+        .filter(|&i| in_degree[i] == 0)
+        .collect::<VecDeque<_>>();
+    let mut visited = vec![false; count];
+    let mut order = Vec::with_capacity(count);
+
+    while let Some(i) = queue.pop_front() {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+        order.push(i);
+        for &j in &edges[i] {
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                queue.push_back(j);
+            }
+        }
+    }
+    // An unexpected cycle would leave nodes unvisited; fall back to original order for those.
+    for i in 0..count {
+        if !visited[i] {
+            order.push(i);
+        }
+    }
+
+    order.into_iter().map(|i| entries[i].clone()).collect()
+}
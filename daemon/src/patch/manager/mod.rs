@@ -14,16 +14,20 @@ use parking_lot::RwLock;
 use syscare_abi::{PatchStatus, PatchType};
 use syscare_common::util::{fs, serde};
 
+mod conflict;
 mod info_ext;
 mod kernel_patch_driver;
 mod patch;
 mod patch_driver;
 mod user_patch_driver;
+mod watcher;
 
+use conflict::{find_conflicts, topological_restore_order, PatchConflictError};
 use kernel_patch_driver::KernelPatchDriver;
 pub use patch::Patch;
 use patch_driver::PatchDriver;
 use user_patch_driver::UserPatchDriver;
+pub use watcher::PatchInstallWatcher;
 
 const PATCH_INSTALL_DIR: &str = "patches";
 const PATCH_STATUS_FILE: &str = "patch_status";
@@ -258,12 +262,12 @@ impl PatchManager {
             }
         };
 
-        /*
-         * To ensure that we won't load multiple patches for same target at the same time,
-         * we take a sort operation of the status to make sure do REMOVE operation at first
-         */
+        // To ensure that we won't load multiple patches for the same target at the same
+        // time, order the restore steps with a topological sort over the per-target
+        // conflict graph, so REMOVE/DEACTIVE steps always run before any conflicting
+        // APPLY/ACTIVE step on the same target.
         info!("Restoring all patch status...");
-        let mut restore_list = status_map
+        let restore_list = status_map
             .into_iter()
             .filter_map(|(uuid, status)| match self.find_patch_by_uuid(&uuid) {
                 Ok(patch) => {
@@ -283,13 +287,7 @@ impl PatchManager {
                 }
             })
             .collect::<Vec<_>>();
-        restore_list.sort_by(|(lhs_patch, lhs_status), (rhs_patch, rhs_status)| {
-            match lhs_status.cmp(rhs_status) {
-                std::cmp::Ordering::Less => std::cmp::Ordering::Less,
-                std::cmp::Ordering::Equal => lhs_patch.cmp(rhs_patch),
-                std::cmp::Ordering::Greater => std::cmp::Ordering::Greater,
-            }
-        });
+        let restore_list = topological_restore_order(restore_list);
 
         for (patch, target_status) in restore_list {
             debug!(
@@ -303,8 +301,12 @@ impl PatchManager {
         Ok(())
     }
 
+    pub fn patch_install_dir(&self) -> PathBuf {
+        self.patch_root.read().join(PATCH_INSTALL_DIR)
+    }
+
     pub fn rescan(&self) -> Result<()> {
-        let patch_install_dir = self.patch_root.read().join(PATCH_INSTALL_DIR);
+        let patch_install_dir = self.patch_install_dir();
         let mut entry_map = self.entry_map.write();
 
         // Add new patches
@@ -339,6 +341,24 @@ impl PatchManager {
             return Ok(target_status);
         }
 
+        let conflicts = {
+            let entry_map = self.entry_map.read();
+            find_conflicts(
+                patch,
+                target_status,
+                entry_map.values().map(|entry| (&entry.patch, entry.status)),
+            )
+        };
+        if !conflicts.is_empty() {
+            return Err(PatchConflictError {
+                requested_patch: patch.to_string(),
+                requested_status: target_status,
+                target_name: patch.target_name.clone(),
+                conflicts,
+            }
+            .into());
+        }
+
         match TRANSITION_MAP.get(&(current_status, target_status)) {
             Some(action_list) => {
                 debug!(
@@ -346,7 +366,9 @@ impl PatchManager {
                     patch, current_status, status
                 );
                 for action in action_list {
-                    action(self, patch)?;
+                    if let Err(e) = action(self, patch) {
+                        return Err(self.rollback_transition(patch, current_status, e));
+                    }
                 }
             }
             None => {
@@ -364,6 +386,75 @@ impl PatchManager {
 
         Ok(new_status)
     }
+
+    /// Attempts to drive `patch` back to `original_status` after a transition action failed
+    /// partway through, so a failed `apply`/`accept` doesn't leave the patch stranded in an
+    /// intermediate state. Always returns an error: either the original failure annotated
+    /// with the rollback outcome, or (if rollback itself fails) both failures chained
+    /// together so operators know whether the system is left in a clean or degraded state.
+    fn rollback_transition(
+        &self,
+        patch: &Patch,
+        original_status: PatchStatus,
+        cause: anyhow::Error,
+    ) -> anyhow::Error {
+        warn!(
+            "Patch \"{}\": Transition failed, attempting rollback to \"{}\"...",
+            patch, original_status
+        );
+
+        let failed_status = match self.get_patch_status(patch) {
+            Ok(status) => status,
+            Err(e) => {
+                return cause.context(format!(
+                    "Patch \"{}\": Transition failed and current status could not be \
+                     determined ({:?}); the system may be left in a degraded state",
+                    patch, e
+                ));
+            }
+        };
+        if failed_status == original_status {
+            return cause.context(format!(
+                "Patch \"{}\": Transition failed, patch remains at \"{}\"",
+                patch, original_status
+            ));
+        }
+
+        match TRANSITION_MAP.get(&(failed_status, original_status)) {
+            Some(action_list) => {
+                for action in action_list {
+                    if let Err(rollback_err) = action(self, patch) {
+                        return cause.context(format!(
+                            "Patch \"{}\": Rollback from \"{}\" to \"{}\" also failed ({:?}); \
+                             the system is left in a degraded state",
+                            patch, failed_status, original_status, rollback_err
+                        ));
+                    }
+                }
+                match self.get_patch_status(patch) {
+                    Ok(status) if status == original_status => cause.context(format!(
+                        "Patch \"{}\": Transition failed, rolled back to \"{}\"",
+                        patch, original_status
+                    )),
+                    Ok(status) => cause.context(format!(
+                        "Patch \"{}\": Transition failed, rollback left patch at \"{}\" \
+                         instead of \"{}\"; the system is left in a degraded state",
+                        patch, status, original_status
+                    )),
+                    Err(e) => cause.context(format!(
+                        "Patch \"{}\": Transition failed and post-rollback status could not \
+                         be determined ({:?}); the system may be left in a degraded state",
+                        patch, e
+                    )),
+                }
+            }
+            None => cause.context(format!(
+                "Patch \"{}\": Transition failed at \"{}\" and no rollback path to \"{}\" \
+                 exists; the system is left in a degraded state",
+                patch, failed_status, original_status
+            )),
+        }
+    }
 }
 
 impl PatchManager {
@@ -430,10 +521,79 @@ impl PatchManager {
             .collect::<Vec<_>>();
 
         if match_result.is_empty() {
-            bail!("Cannot match any patch of \"{}\"", identifier);
+            let suggestions = self.suggest_patch_names(identifier);
+            if suggestions.is_empty() {
+                bail!("Cannot match any patch of \"{}\"", identifier);
+            }
+            bail!(
+                "Cannot match any patch of \"{}\"; did you mean {}?",
+                identifier,
+                suggestions
+                    .iter()
+                    .map(|name| format!("\"{}\"", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
         }
         Ok(match_result)
     }
+
+    /// Finds patch names (entity/patch/target) close to `identifier` by edit distance, for
+    /// use in "did you mean...?" suggestions when an exact match fails.
+    fn suggest_patch_names(&self, identifier: &str) -> Vec<String> {
+        let max_distance = std::cmp::max(3, identifier.chars().count() / 3);
+
+        let mut candidates = self
+            .entry_map
+            .read()
+            .values()
+            .flat_map(|entry| {
+                let patch = &entry.patch;
+                [
+                    patch.entity_name.clone(),
+                    patch.patch_name.clone(),
+                    patch.target_name.clone(),
+                ]
+            })
+            .filter(|name| name != identifier)
+            .map(|name| {
+                let distance = levenshtein_distance(identifier, &name);
+                (name, distance)
+            })
+            .filter(|(_, distance)| *distance <= max_distance)
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|(lhs_name, lhs_distance), (rhs_name, rhs_distance)| {
+            lhs_distance.cmp(rhs_distance).then_with(|| lhs_name.cmp(rhs_name))
+        });
+        candidates.dedup_by(|lhs, rhs| lhs.0 == rhs.0);
+
+        candidates.into_iter().map(|(name, _)| name).collect()
+    }
+}
+
+/// Classic Levenshtein DP keeping only two rows (previous and current), as used by e.g.
+/// Cargo's "did you mean" suggestions.
+fn levenshtein_distance(lhs: &str, rhs: &str) -> usize {
+    let lhs = lhs.chars().collect::<Vec<_>>();
+    let rhs = rhs.chars().collect::<Vec<_>>();
+
+    let mut prev_row = (0..=rhs.len()).collect::<Vec<_>>();
+    let mut cur_row = vec![0usize; rhs.len() + 1];
+
+    for (i, lhs_ch) in lhs.iter().enumerate() {
+        cur_row[0] = i + 1;
+        for (j, rhs_ch) in rhs.iter().enumerate() {
+            let substitution_cost = usize::from(lhs_ch != rhs_ch);
+            cur_row[j + 1] = std::cmp::min(
+                std::cmp::min(cur_row[j] + 1, prev_row[j + 1] + 1),
+                prev_row[j] + substitution_cost,
+            );
+        }
+        std::mem::swap(&mut prev_row, &mut cur_row);
+    }
+
+    prev_row[rhs.len()]
 }
 
 impl PatchManager {
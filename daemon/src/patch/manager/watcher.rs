@@ -0,0 +1,109 @@
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use log::{debug, error, info};
+use notify::{event::ModifyKind, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::RwLock;
+
+use super::PatchManager;
+
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watches the patch install directory for create/remove/move events and, once a burst of
+/// filesystem activity settles, triggers `PatchManager::rescan` and `restore_patch_status` —
+/// so patch RPMs dropped in after daemon start are picked up automatically, without a manual
+/// rescan or a daemon restart.
+pub struct PatchInstallWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl PatchInstallWatcher {
+    /// Starts the watcher with the default debounce interval.
+    pub fn new(
+        patch_manager: Arc<RwLock<PatchManager>>,
+        restore_accepted_only: bool,
+    ) -> Result<Self> {
+        Self::with_debounce(patch_manager, restore_accepted_only, DEFAULT_DEBOUNCE)
+    }
+
+    pub fn with_debounce(
+        patch_manager: Arc<RwLock<PatchManager>>,
+        restore_accepted_only: bool,
+        debounce: Duration,
+    ) -> Result<Self> {
+        let watch_dir = patch_manager.read().patch_install_dir();
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(tx).context("Failed to create patch install watcher")?;
+        watcher
+            .watch(&watch_dir, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch \"{}\"", watch_dir.display()))?;
+
+        thread::Builder::new()
+            .name("patch-install-watcher".to_string())
+            .spawn(move || Self::event_loop(rx, patch_manager, debounce, restore_accepted_only))
+            .context("Failed to start patch install watcher thread")?;
+
+        info!(
+            "Watching \"{}\" for patch install changes (debounce {:?})",
+            watch_dir.display(),
+            debounce
+        );
+        Ok(Self { _watcher: watcher })
+    }
+
+    fn event_loop(
+        rx: mpsc::Receiver<notify::Result<notify::Event>>,
+        patch_manager: Arc<RwLock<PatchManager>>,
+        debounce: Duration,
+        restore_accepted_only: bool,
+    ) {
+        let mut pending = false;
+        let mut last_event = Instant::now();
+
+        loop {
+            match rx.recv_timeout(debounce) {
+                Ok(Ok(event)) => {
+                    if matches!(
+                        event.kind,
+                        EventKind::Create(_)
+                            | EventKind::Remove(_)
+                            | EventKind::Modify(ModifyKind::Name(_))
+                    ) {
+                        pending = true;
+                        last_event = Instant::now();
+                    }
+                }
+                Ok(Err(e)) => error!("Patch install watcher error: {}", e),
+                Err(RecvTimeoutError::Timeout) => {
+                    if pending && (last_event.elapsed() >= debounce) {
+                        pending = false;
+                        Self::settle(&patch_manager, restore_accepted_only);
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    fn settle(patch_manager: &Arc<RwLock<PatchManager>>, restore_accepted_only: bool) {
+        debug!("Patch install directory settled, rescanning...");
+        let manager = patch_manager.read();
+
+        if let Err(e) = manager.rescan() {
+            error!("Failed to rescan patches: {:?}", e);
+            return;
+        }
+        if let Err(e) = manager.restore_patch_status(restore_accepted_only) {
+            error!("Failed to restore patch status: {:?}", e);
+        }
+    }
+}
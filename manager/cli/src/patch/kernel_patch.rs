@@ -1,5 +1,8 @@
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use std::thread;
 
+use anyhow::{bail, Context, Result};
 use log::{trace, debug};
 
 use crate::util::sys;
@@ -25,9 +28,18 @@ const KPATCH_PATCH_SEC_TYPE: &str = "modules_object_t";
 const KPATCH_MGNT_DIR:  &str = "/sys/kernel/livepatch";
 const KPATCH_MGNT_FILE: &str = "enabled";
 
+const KPATCH_TRANSITION_FILE: &str = "transition";
+
 const KPATCH_STATUS_DISABLED: &str = "0";
 const KPATCH_STATUS_ENABLED:  &str = "1";
 
+const KPATCH_STATUS_FILE_MODE: u32 = 0o644;
+
+const KPATCH_TRANSITION_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const KPATCH_TRANSITION_TIMEOUT:       Duration = Duration::from_secs(30);
+
+const PROC_DIR: &str = "/proc";
+
 impl<'a> KernelPatchAdapter<'a> {
     pub fn new(patch: &'a Patch) -> Self {
         Self { patch }
@@ -41,8 +53,8 @@ impl<'a> KernelPatchAdapter<'a> {
         self.patch.root_dir.join(patch_name)
     }
 
-    fn set_patch_security_context(&self) -> std::io::Result<()> {
-        if selinux::get_enforce()? == 0 {
+    fn set_patch_security_context(&self) -> Result<()> {
+        if selinux::get_enforce().context("Failed to read SELinux enforcement mode")? == 0 {
             debug!("SELinux is permissive");
             return Ok(());
         }
@@ -50,12 +62,14 @@ impl<'a> KernelPatchAdapter<'a> {
 
         let patch      = self.patch;
         let patch_file = self.get_patch_file();
-        let sec_type   = selinux::get_security_context_type(patch_file.as_path())?;
+        let sec_type   = selinux::get_security_context_type(patch_file.as_path())
+            .with_context(|| format!("Failed to read security context type of \"{}\"", patch_file.display()))?;
         if sec_type != KPATCH_PATCH_SEC_TYPE {
             debug!("set patch \"{}\" security context type to \"{}\"",
                 patch, KPATCH_PATCH_SEC_TYPE
             );
-            selinux::set_security_context_type(&patch_file, KPATCH_PATCH_SEC_TYPE)?;
+            selinux::set_security_context_type(&patch_file, KPATCH_PATCH_SEC_TYPE)
+                .with_context(|| format!("Failed to set security context type of \"{}\"", patch_file.display()))?;
         }
 
         Ok(())
@@ -69,7 +83,89 @@ impl<'a> KernelPatchAdapter<'a> {
             .join(KPATCH_MGNT_FILE)
     }
 
-    fn read_patch_status(&self) -> std::io::Result<PatchStatus> {
+    fn patch_transition_interface(&self) -> PathBuf {
+        let patch_name = self.patch.short_name().replace('-', "_");
+
+        PathBuf::from(KPATCH_MGNT_DIR)
+            .join(patch_name)
+            .join(KPATCH_TRANSITION_FILE)
+    }
+
+    fn is_transition_in_progress(&self) -> Result<bool> {
+        let transition_file = self.patch_transition_interface();
+
+        let read_result = fs::read_to_string(&transition_file);
+        match read_result {
+            Ok(s) => Ok(s.trim() != KPATCH_STATUS_DISABLED),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("Failed to read \"{}\"", transition_file.display())),
+        }
+    }
+
+    /// Scans `/proc/*/patch_state` (falling back to `/proc/*/stack_state` on older kernels) for
+    /// tasks still blocking the livepatch transition, logging their pid and comm at debug level
+    /// so operators can see why a transition is stuck instead of just watching it time out.
+    fn log_blocking_tasks(&self) {
+        let entries = match std::fs::read_dir(PROC_DIR) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Failed to read \"{}\": {}", PROC_DIR, e);
+                return;
+            },
+        };
+
+        // While a transition is in progress, `enabled` holds the state tasks are moving to; a
+        // task whose `patch_state` differs from it has not been migrated yet.
+        let target_state = fs::read_to_string(self.patch_sys_interface())
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|_| KPATCH_STATUS_ENABLED.to_string());
+
+        for entry in entries.flatten() {
+            let pid = entry.file_name();
+            let pid_str = pid.to_string_lossy();
+            if !pid_str.chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+
+            let proc_dir = entry.path();
+            let state = std::fs::read_to_string(proc_dir.join("patch_state"))
+                .or_else(|_| std::fs::read_to_string(proc_dir.join("stack_state")));
+            let blocking = match state {
+                Ok(state) => state.trim() != target_state,
+                Err(_) => continue,
+            };
+            if !blocking {
+                continue;
+            }
+
+            let comm = std::fs::read_to_string(proc_dir.join("comm"))
+                .map(|comm| comm.trim().to_string())
+                .unwrap_or_else(|_| "<unknown>".to_string());
+            debug!("task {} ({}) is blocking livepatch transition of \"{}\"", pid_str, comm, self.patch);
+        }
+    }
+
+    /// Polls the `transition` sysfs file until the kernel finishes migrating tasks onto (or off)
+    /// the patch, logging blocking tasks while it's in progress. Returns an error instead of
+    /// silently claiming success if the transition hasn't settled within `timeout`.
+    fn wait_for_transition(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+
+        while self.is_transition_in_progress()? {
+            if Instant::now() >= deadline {
+                bail!(
+                    "patch \"{}\" livepatch transition did not settle within {:?}",
+                    self.patch, timeout
+                );
+            }
+            self.log_blocking_tasks();
+            thread::sleep(KPATCH_TRANSITION_POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    fn read_patch_status(&self) -> Result<PatchStatus> {
         let sys_file_path = self.patch_sys_interface();
 
         let read_result = fs::read_to_string(&sys_file_path);
@@ -81,12 +177,7 @@ impl<'a> KernelPatchAdapter<'a> {
                 let patch_status = match status {
                     KPATCH_STATUS_DISABLED => PatchStatus::Deactived,
                     KPATCH_STATUS_ENABLED  => PatchStatus::Actived,
-                    _ => {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("status \"{}\" is invalid", status)
-                        ));
-                    }
+                    _ => bail!("status \"{}\" read from \"{}\" is invalid", status, sys_file_path.display()),
                 };
 
                 Ok(patch_status)
@@ -94,11 +185,11 @@ impl<'a> KernelPatchAdapter<'a> {
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 Ok(PatchStatus::NotApplied)
             },
-            Err(e) => Err(e)
+            Err(e) => Err(e).with_context(|| format!("Failed to read \"{}\"", sys_file_path.display())),
         }
     }
 
-    fn write_patch_status(&self, status: PatchStatus) -> std::io::Result<()> {
+    fn write_patch_status(&self, status: PatchStatus) -> Result<()> {
         let sys_file_path = self.patch_sys_interface();
         let status_str = match status {
             PatchStatus::NotApplied | PatchStatus::Deactived => KPATCH_STATUS_DISABLED,
@@ -106,13 +197,21 @@ impl<'a> KernelPatchAdapter<'a> {
         };
         trace!("write file \"{}\": {}", sys_file_path.display(), status_str);
 
-        fs::write(&sys_file_path, status_str)
+        fs::write_atomic(&sys_file_path, status_str, KPATCH_STATUS_FILE_MODE)
+            .with_context(|| format!("Failed to write \"{}\"", sys_file_path.display()))
     }
 }
 
-impl PatchActionAdapter for KernelPatchAdapter<'_> {
-    fn check_compatibility(&self) -> std::io::Result<()> {
-        let kernel_version = sys::get_kernel_version()?;
+/// Converts the contextual `anyhow` errors produced internally into the `std::io::Result` that
+/// `PatchActionAdapter` is declared with, so this adapter keeps the richer error chaining
+/// without changing the trait's (or any other implementor's) signature.
+fn to_io_error(err: anyhow::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, format!("{:#}", err))
+}
+
+impl KernelPatchAdapter<'_> {
+    fn check_compatibility_impl(&self) -> Result<()> {
+        let kernel_version = sys::get_kernel_version().context("Failed to read running kernel version")?;
         let current_kernel = format!("kernel-{}", kernel_version);
         debug!("current kernel: \"{}\"", current_kernel);
 
@@ -120,60 +219,70 @@ impl PatchActionAdapter for KernelPatchAdapter<'_> {
         debug!("patch target:  \"{}\"", patch_target);
 
         if patch_target != current_kernel {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("current kernel \"{}\" is incompatible", kernel_version)
-            ));
+            bail!("patch \"{}\" targets \"{}\", but current kernel \"{}\" is incompatible", self.patch, patch_target, kernel_version);
         }
 
         Ok(())
     }
 
-    fn status(&self) -> std::io::Result<PatchStatus> {
-        self.read_patch_status()
-    }
-
-    fn apply(&self) -> std::io::Result<()> {
+    fn apply_impl(&self) -> Result<()> {
         self.set_patch_security_context()?;
 
         let patch_file = self.get_patch_file();
         let exit_status = INSMOD.execvp(
-            ExternCommandArgs::new().arg(patch_file)
-        )?;
+            ExternCommandArgs::new().arg(patch_file.clone())
+        ).with_context(|| format!("Failed to execute insmod on \"{}\"", patch_file.display()))?;
 
         if exit_status.exit_code() != 0 {
             debug!("{}", exit_status.stderr());
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::BrokenPipe,
-                exit_status.stderr(),
-            ));
+            bail!("Failed to insert patch module \"{}\": {}", patch_file.display(), exit_status.stderr());
         }
 
-        Ok(())
+        self.wait_for_transition(KPATCH_TRANSITION_TIMEOUT)
     }
 
-    fn remove(&self) -> std::io::Result<()> {
+    fn remove_impl(&self) -> Result<()> {
         let patch_file  = self.get_patch_file();
         let exit_status = RMMOD.execvp(
-            ExternCommandArgs::new().arg(patch_file)
-        )?;
+            ExternCommandArgs::new().arg(patch_file.clone())
+        ).with_context(|| format!("Failed to execute rmmod on \"{}\"", patch_file.display()))?;
 
         if exit_status.exit_code() != 0 {
             debug!("{}", exit_status.stderr());
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::BrokenPipe,
-                exit_status.stderr(),
-            ));
+            bail!("Failed to remove patch module \"{}\": {}", patch_file.display(), exit_status.stderr());
         }
 
         Ok(())
     }
 
+    fn active_impl(&self) -> Result<()> {
+        self.write_patch_status(PatchStatus::Actived)?;
+        self.wait_for_transition(KPATCH_TRANSITION_TIMEOUT)
+    }
+}
+
+impl PatchActionAdapter for KernelPatchAdapter<'_> {
+    fn check_compatibility(&self) -> std::io::Result<()> {
+        self.check_compatibility_impl().map_err(to_io_error)
+    }
+
+    fn status(&self) -> std::io::Result<PatchStatus> {
+        self.read_patch_status().map_err(to_io_error)
+    }
+
+    fn apply(&self) -> std::io::Result<()> {
+        self.apply_impl().map_err(to_io_error)
+    }
+
+    fn remove(&self) -> std::io::Result<()> {
+        self.remove_impl().map_err(to_io_error)
+    }
+
     fn active(&self) -> std::io::Result<()> {
-        self.write_patch_status(PatchStatus::Actived)
+        self.active_impl().map_err(to_io_error)
     }
 
     fn deactive(&self) -> std::io::Result<()> {
-        self.write_patch_status(PatchStatus::Deactived)
+        self.write_patch_status(PatchStatus::Deactived).map_err(to_io_error)
     }
 }
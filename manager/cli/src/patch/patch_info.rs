@@ -15,6 +15,23 @@ pub enum PatchType {
     KernelPatch,
 }
 
+/// Compression format a patch package was packed with, so downstream tooling knows how
+/// to decompress it without re-sniffing the archive.
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+pub enum CompressionFormat {
+    Gzip,
+    Xz,
+    None,
+}
+
+impl std::fmt::Display for CompressionFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{:?}", self))
+    }
+}
+
 impl std::fmt::Display for PatchType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{:?}", self))
@@ -44,9 +61,74 @@ pub struct PatchInfo {
     pub incremental: bool,
     pub builder:     String,
     pub patches:     Vec<PatchFile>,
+    pub compression_format: CompressionFormat,
+    pub compression_level:  u32,
 }
 
 impl PatchInfo {
+    /// Combine N existing incremental `PatchInfo`s for the same target into a single
+    /// cumulative package, mirroring how rust-installer's combiner merges component
+    /// tarballs into one installer. The patch/target-elf lists are deduplicated and
+    /// concatenated in order, so `print_log` still reports every original `PatchFile`
+    /// (and its digest) that went into the bundle.
+    pub fn combine(patches: &[PatchInfo]) -> std::io::Result<PatchInfo> {
+        let first = patches.first().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Cannot combine an empty patch list",
+            )
+        })?;
+
+        for patch in &patches[1..] {
+            if (&patch.target.name, &patch.target.version, &patch.target.release, &patch.target.arch)
+                != (&first.target.name, &first.target.version, &first.target.release, &first.target.arch)
+            {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "Cannot combine patches for different targets: '{}' vs '{}'",
+                        first.target.short_name(),
+                        patch.target.short_name()
+                    ),
+                ));
+            }
+        }
+
+        let mut seen_files = std::collections::HashSet::new();
+        let mut merged_patch_files = Vec::new();
+        let mut merged_target_elfs = HashMap::new();
+
+        for patch in patches {
+            for patch_file in &patch.patches {
+                if seen_files.insert(patch_file.name.clone()) {
+                    merged_patch_files.push(patch_file.clone());
+                }
+            }
+            for (elf_name, elf_path) in &patch.target_elfs {
+                merged_target_elfs
+                    .entry(elf_name.clone())
+                    .or_insert_with(|| elf_path.clone());
+            }
+        }
+
+        Ok(PatchInfo {
+            name: first.name.clone(),
+            version: first.version,
+            release: first.release.clone(),
+            arch: first.arch.clone(),
+            kind: first.kind,
+            target: first.target.clone(),
+            target_elfs: merged_target_elfs,
+            license: first.license.clone(),
+            description: first.description.clone(),
+            incremental: true,
+            builder: first.builder.clone(),
+            patches: merged_patch_files,
+            compression_format: first.compression_format,
+            compression_level: first.compression_level,
+        })
+    }
+
     pub fn print_log(&self, level: log::Level) {
         const PATCH_FLAG_NONE: &str = "(none)";
 
@@ -75,6 +157,7 @@ impl PatchInfo {
         log!(level, "license:     {}", self.license);
         log!(level, "description: {}", self.description);
         log!(level, "builder:     {}", self.builder);
+        log!(level, "compression: {} (level {})", self.compression_format, self.compression_level);
         log!(level, "");
         log!(level, "patch list:");
         for patch_file in &self.patches {
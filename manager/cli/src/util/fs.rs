@@ -0,0 +1,55 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    std::fs::read_to_string(path.as_ref())
+}
+
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> std::io::Result<()> {
+    std::fs::write(path.as_ref(), contents)
+}
+
+/// sysfs and procfs nodes can't be renamed into place (they're not regular files, and many
+/// don't even support `O_CREAT`), so a write there has to stay a direct, single `write(2)`.
+fn is_pseudo_fs<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    path.starts_with("/sys") || path.starts_with("/proc")
+}
+
+/// Writes `contents` to `path` with the given unix permission bits, durably: for a regular
+/// file, this opens a sibling temp file with `mode` already applied, writes, `fsync`s, then
+/// renames it into place, so a crash or power loss never leaves a half-written file behind.
+/// sysfs/procfs nodes can't be renamed into place, so those fall back to a direct write.
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C, mode: u32) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let contents = contents.as_ref();
+
+    if is_pseudo_fs(path) {
+        let mut file = OpenOptions::new().write(true).mode(mode).open(path)?;
+        return file.write_all(contents);
+    }
+
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = path.file_name().ok_or_else(|| std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("\"{}\" has no file name", path.display()),
+    ))?;
+
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = parent.join(tmp_name);
+
+    let mut tmp_file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(&tmp_path)?;
+    tmp_file.write_all(contents)?;
+    tmp_file.sync_all()?;
+    drop(tmp_file);
+
+    std::fs::rename(&tmp_path, path)
+}
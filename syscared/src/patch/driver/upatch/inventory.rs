@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: Mulan PSL v2
+/*
+ * Copyright (c) 2024 Huawei Technologies Co., Ltd.
+ * syscared is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the Mulan PSL v2.
+ * You may obtain a copy of Mulan PSL v2 at:
+ *         http://license.coscl.org.cn/MulanPSL2
+ *
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+ * EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+ * MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ */
+
+use std::{
+    os::linux::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use sysinfo::{Pid, PidExt, Process, ProcessExt, System, SystemExt};
+
+/// A single running process, as seen by the last inventory refresh.
+#[derive(Debug, Clone)]
+pub struct ProcessEntry {
+    pub pid: i32,
+    pub exe_path: Option<PathBuf>,
+    pub cmdline: Vec<String>,
+    pub start_time: u64,
+    pub user: Option<String>,
+}
+
+impl ProcessEntry {
+    fn from_process(pid: i32, process: &Process) -> Self {
+        Self {
+            pid,
+            exe_path: Some(process.exe().to_path_buf()).filter(|p| !p.as_os_str().is_empty()),
+            cmdline: process.cmd().to_vec(),
+            start_time: process.start_time(),
+            user: process.user_id().map(|uid| uid.to_string()),
+        }
+    }
+
+    /// Short, human-friendly description used for log messages, e.g. "nginx (pid 1234)".
+    pub fn describe(&self) -> String {
+        let name = self
+            .cmdline
+            .first()
+            .cloned()
+            .or_else(|| {
+                self.exe_path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+            })
+            .unwrap_or_else(|| "<unknown>".to_string());
+
+        format!("{} (pid {})", name, self.pid)
+    }
+}
+
+/// A one-shot snapshot of `/proc`, taken via `sysinfo` instead of hand-rolled parsing.
+///
+/// A single `ProcessInventory` is meant to be built once per patch operation (apply,
+/// active, deactive, or a monitor-driven `patch_new_process` pass) and reused for every
+/// target looked up during that operation, instead of re-scanning `/proc` per target.
+pub struct ProcessInventory {
+    processes: IndexMap<i32, ProcessEntry>,
+}
+
+impl ProcessInventory {
+    /// Take a fresh snapshot of all running processes.
+    pub fn collect() -> Self {
+        let mut system = System::new();
+        system.refresh_processes();
+
+        let processes = system
+            .processes()
+            .iter()
+            .map(|(pid, process)| {
+                let pid = pid.as_u32() as i32;
+                (pid, ProcessEntry::from_process(pid, process))
+            })
+            .collect();
+
+        Self { processes }
+    }
+
+    pub fn get(&self, pid: i32) -> Option<&ProcessEntry> {
+        self.processes.get(&pid)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ProcessEntry> {
+        self.processes.values()
+    }
+
+    /// Find every process whose binary is (or, via `/proc/<pid>/map_files`, has loaded)
+    /// `target_elf`. The exe-path check covers the common case cheaply; the inode-based
+    /// `map_files` walk is still required to catch libraries pulled in via `dlopen`.
+    pub fn find_target_processes<P: AsRef<Path>>(&self, target_elf: P) -> Vec<&ProcessEntry> {
+        let target_path = target_elf.as_ref();
+        let target_inode = match target_path.metadata() {
+            Ok(metadata) => metadata.st_ino(),
+            Err(_) => return Vec::new(),
+        };
+
+        self.processes
+            .values()
+            .filter(|entry| {
+                if entry.exe_path.as_deref() == Some(target_path) {
+                    return true;
+                }
+                Self::maps_target_inode(entry.pid, target_inode)
+            })
+            .collect()
+    }
+
+    fn maps_target_inode(pid: i32, target_inode: u64) -> bool {
+        let map_files_dir = format!("/proc/{}/map_files", pid);
+        let map_files = match syscare_common::fs::list_symlinks(
+            map_files_dir,
+            syscare_common::fs::TraverseOptions { recursive: false },
+        ) {
+            Ok(files) => files,
+            Err(_) => return false,
+        };
+
+        map_files.iter().any(|mapped_file| {
+            mapped_file
+                .read_link()
+                .and_then(|file_path| Ok(file_path.metadata()?.st_ino()))
+                .map(|inode| inode == target_inode)
+                .unwrap_or(false)
+        })
+    }
+}
@@ -13,34 +13,40 @@
  */
 
 use std::{
-    ffi::OsStr,
     fmt::Write,
-    os::linux::fs::MetadataExt,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
 use anyhow::{bail, ensure, Context, Result};
-use indexmap::{indexset, IndexMap, IndexSet};
+use indexmap::{IndexMap, IndexSet};
 use log::{debug, info, warn};
 use parking_lot::RwLock;
+use signal_hook::consts::TERM_SIGNALS;
 use uuid::Uuid;
 
+use serde::{Deserialize, Serialize};
+
 use syscare_abi::PatchStatus;
-use syscare_common::{fs, util::digest};
+use syscare_common::util::{digest, serde as common_serde};
 
 use crate::patch::{driver::upatch::entity::PatchEntity, entity::UserPatch};
 
 mod entity;
+mod inventory;
 mod monitor;
 mod sys;
 mod target;
+mod verify;
 
+use inventory::{ProcessEntry, ProcessInventory};
 use monitor::UserPatchMonitor;
 use target::PatchTarget;
+pub use verify::{PatchVerifyResult, VerifyKind, VerifyRecord};
 
 pub struct UserPatchDriver {
     status_map: IndexMap<Uuid, PatchStatus>,
+    patch_records: IndexMap<Uuid, PatchRecord>,
     target_map: Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>,
     monitor: UserPatchMonitor,
 }
@@ -48,10 +54,12 @@ pub struct UserPatchDriver {
 impl UserPatchDriver {
     pub fn new() -> Result<Self> {
         let status_map = IndexMap::new();
+        let patch_records = IndexMap::new();
         let target_map = Arc::new(RwLock::new(IndexMap::new()));
         let monitor = UserPatchMonitor::new(target_map.clone(), Self::patch_new_process)?;
         let instance = Self {
             status_map,
+            patch_records,
             target_map,
             monitor,
         };
@@ -60,6 +68,124 @@ impl UserPatchDriver {
     }
 }
 
+/// Everything needed to re-establish one patch's in-memory bookkeeping after a restart: which
+/// target it patches, the patch file backing it, and the function symbols it touches (the same
+/// list `active`/`deactive` pass to `PatchTarget::add_functions`/`remove_functions`).
+#[derive(Clone, Serialize, Deserialize)]
+struct PatchRecord {
+    target_elf: PathBuf,
+    patch_file: PathBuf,
+    functions: Vec<String>,
+}
+
+/// On-disk snapshot of `UserPatchDriver` state, written by [`UserPatchDriver::save_state`] and
+/// read back by [`UserPatchDriver::load_state`].
+#[derive(Serialize, Deserialize)]
+struct DriverStateSnapshot {
+    status_map: IndexMap<Uuid, PatchStatus>,
+    patch_records: IndexMap<Uuid, PatchRecord>,
+}
+
+impl UserPatchDriver {
+    /// Serialize `status_map` and the per-target patch/function records to `path`. The handler
+    /// that [`install_shutdown_persistence`](Self::install_shutdown_persistence) registers for
+    /// the termination signals calls it before the process exits; the `Save` command does not
+    /// call it, and a crash that raises no termination signal leaves no snapshot.
+    pub fn save_state<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let snapshot = DriverStateSnapshot {
+            status_map: self.status_map.clone(),
+            patch_records: self.patch_records.clone(),
+        };
+        common_serde::serialize(&snapshot, path).context("Upatch: Failed to write driver state")
+    }
+
+    /// Restore `status_map` and `target_map` from `path`, reconciling against the filesystem
+    /// only (the live process list is not consulted): records whose target no longer exists on
+    /// disk are dropped together with their status, and `Actived`
+    /// records are replayed back into `target_map` (re-creating the patch entity and its
+    /// function records) so `check_conflict_functions`/`check_override_functions` see the same
+    /// state they would have before the restart, then the target is re-watched.
+    pub fn load_state<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let snapshot: DriverStateSnapshot =
+            common_serde::deserialize(path).context("Upatch: Failed to read driver state")?;
+
+        let mut target_map = self.target_map.write();
+        for (patch_uuid, record) in &snapshot.patch_records {
+            if !record.target_elf.exists() {
+                debug!(
+                    "Upatch: Dropping stale patch target '{}'",
+                    record.target_elf.display()
+                );
+                continue;
+            }
+
+            let patch_target = target_map
+                .entry(record.target_elf.clone())
+                .or_insert_with(PatchTarget::default);
+
+            let status = snapshot
+                .status_map
+                .get(patch_uuid)
+                .copied()
+                .unwrap_or(PatchStatus::NotApplied);
+            if status == PatchStatus::Actived {
+                patch_target.add_patch(*patch_uuid, PatchEntity::new(record.patch_file.clone()));
+                patch_target.add_functions(*patch_uuid, &record.functions);
+            }
+        }
+        drop(target_map);
+
+        for record in snapshot.patch_records.values().filter(|r| r.target_elf.exists()) {
+            if let Err(e) = self.monitor.watch_file(&record.target_elf) {
+                warn!(
+                    "Upatch: Failed to resume watching '{}', {}",
+                    record.target_elf.display(),
+                    e.to_string().to_lowercase(),
+                );
+            }
+        }
+
+        let mut status_map = snapshot.status_map;
+        let mut patch_records = snapshot.patch_records;
+        patch_records.retain(|patch_uuid, record| {
+            let target_exists = record.target_elf.exists();
+            if !target_exists {
+                status_map.remove(patch_uuid);
+            }
+            target_exists
+        });
+        self.status_map = status_map;
+        self.patch_records = patch_records;
+
+        Ok(())
+    }
+
+    /// Registers `SIGTERM`/`SIGINT` handlers that persist the current driver state to
+    /// `snapshot_path` before the process exits, so a `kill`/Ctrl-C during shutdown loses no
+    /// more state than an explicit `Save` would have. Intended to be called once from the
+    /// daemon's startup path, right after the driver is wrapped for shared ownership.
+    pub fn install_shutdown_persistence(
+        driver: Arc<RwLock<Self>>,
+        snapshot_path: PathBuf,
+    ) -> Result<()> {
+        for signal in TERM_SIGNALS {
+            let driver = driver.clone();
+            let snapshot_path = snapshot_path.clone();
+            unsafe {
+                signal_hook::low_level::register(*signal, move || {
+                    if let Err(e) = driver.read().save_state(&snapshot_path) {
+                        eprintln!("Upatch: Failed to save driver state on shutdown: {:?}", e);
+                    }
+                    let _ = signal_hook::low_level::emulate_default_handler(*signal);
+                })
+            }
+            .with_context(|| format!("Failed to register handler for signal {}", signal))?;
+        }
+
+        Ok(())
+    }
+}
+
 impl UserPatchDriver {
     #[inline]
     fn get_patch_status(&self, uuid: &Uuid) -> PatchStatus {
@@ -118,114 +244,68 @@ impl UserPatchDriver {
         Ok(())
     }
 
-    pub fn check_conflict_functions(&self, patch: &UserPatch) -> Result<()> {
-        let conflict_patches = match self.target_map.read().get(&patch.target_elf) {
+    pub fn check_conflict_functions(&self, patch: &UserPatch) -> Result<PatchVerifyResult> {
+        let records = match self.target_map.read().get(&patch.target_elf) {
             Some(target) => target
                 .get_conflicts(&patch.functions)
                 .into_iter()
-                .map(|record| record.uuid)
+                .map(|record| VerifyRecord {
+                    conflicting_uuid: record.uuid,
+                    function_symbol: record.function.clone(),
+                    kind: VerifyKind::Conflict,
+                })
                 .collect(),
-            None => indexset! {},
+            None => Vec::new(),
         };
 
-        ensure!(conflict_patches.is_empty(), {
-            let mut err_msg = String::new();
-
-            writeln!(&mut err_msg, "Upatch: Patch is conflicted with")?;
-            for uuid in conflict_patches.into_iter() {
-                writeln!(&mut err_msg, "* Patch '{}'", uuid)?;
-            }
-            err_msg.pop();
-
-            err_msg
-        });
-        Ok(())
+        Ok(PatchVerifyResult::new(records))
     }
 
-    pub fn check_override_functions(&self, patch: &UserPatch) -> Result<()> {
-        let override_patches = match self.target_map.read().get(&patch.target_elf) {
+    pub fn check_override_functions(&self, patch: &UserPatch) -> Result<PatchVerifyResult> {
+        let records = match self.target_map.read().get(&patch.target_elf) {
             Some(target) => target
                 .get_overrides(&patch.uuid, &patch.functions)
                 .into_iter()
-                .map(|record| record.uuid)
+                .map(|record| VerifyRecord {
+                    conflicting_uuid: record.uuid,
+                    function_symbol: record.function.clone(),
+                    kind: VerifyKind::Override,
+                })
                 .collect(),
-            None => indexset! {},
+            None => Vec::new(),
         };
 
-        ensure!(override_patches.is_empty(), {
-            let mut err_msg = String::new();
-
-            writeln!(&mut err_msg, "Upatch: Patch is overrided by")?;
-            for uuid in override_patches.into_iter() {
-                writeln!(&mut err_msg, "* Patch '{}'", uuid)?;
-            }
-            err_msg.pop();
-
-            err_msg
-        });
-
-        Ok(())
+        Ok(PatchVerifyResult::new(records))
     }
 }
 
 impl UserPatchDriver {
-    #[inline]
-    fn parse_process_id(proc_path: &Path) -> Option<i32> {
-        proc_path
-            .file_name()
-            .and_then(OsStr::to_str)
-            .map(str::parse)
-            .and_then(Result::ok)
-    }
-
+    /// Find every live process currently running (or linking, via `dlopen`) `target_elf`,
+    /// using a fresh process inventory. Prefer `find_target_process_in` when an inventory
+    /// has already been collected for the current patch operation, to avoid re-scanning
+    /// `/proc` once per target.
     fn find_target_process<P: AsRef<Path>>(target_elf: P) -> Result<IndexSet<i32>> {
-        let mut target_pids = IndexSet::new();
-        let target_path = target_elf.as_ref();
-        let target_inode = target_path.metadata()?.st_ino();
-
-        for proc_path in fs::list_dirs("/proc", fs::TraverseOptions { recursive: false })? {
-            let pid = match Self::parse_process_id(&proc_path) {
-                Some(pid) => pid,
-                None => continue,
-            };
-            let exec_path = match fs::read_link(format!("/proc/{}/exe", pid)) {
-                Ok(file_path) => file_path,
-                Err(_) => continue,
-            };
-            // Try to match binary path
-            if exec_path == target_path {
-                target_pids.insert(pid);
-                continue;
-            }
-            // Try to match mapped files
-            let map_files = fs::list_symlinks(
-                format!("/proc/{}/map_files", pid),
-                fs::TraverseOptions { recursive: false },
-            )?;
-            for mapped_file in map_files {
-                if let Ok(mapped_inode) = mapped_file
-                    .read_link()
-                    .and_then(|file_path| Ok(file_path.metadata()?.st_ino()))
-                {
-                    if mapped_inode == target_inode {
-                        target_pids.insert(pid);
-                        break;
-                    }
-                };
-            }
-        }
+        let inventory = ProcessInventory::collect();
+        Ok(Self::find_target_process_in(&inventory, target_elf))
+    }
 
-        Ok(target_pids)
+    fn find_target_process_in<P: AsRef<Path>>(
+        inventory: &ProcessInventory,
+        target_elf: P,
+    ) -> IndexSet<i32> {
+        inventory
+            .find_target_processes(target_elf)
+            .into_iter()
+            .map(|entry| entry.pid)
+            .collect()
     }
 
     fn patch_new_process(
         target_map: Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>,
         target_elf: &Path,
     ) {
-        let process_list = match Self::find_target_process(target_elf) {
-            Ok(pids) => pids,
-            Err(_) => return,
-        };
+        let inventory = ProcessInventory::collect();
+        let process_list = Self::find_target_process_in(&inventory, target_elf);
 
         let mut target_map = target_map.write();
         let patch_target = match target_map.get_mut(target_elf) {
@@ -243,7 +323,13 @@ impl UserPatchDriver {
                     "Upatch: Activating patch '{}' ({}) for process {:?}",
                     patch_uuid,
                     target_elf.display(),
-                    need_actived,
+                    need_actived
+                        .iter()
+                        .map(|pid| inventory
+                            .get(*pid)
+                            .map(ProcessEntry::describe)
+                            .unwrap_or_else(|| pid.to_string()))
+                        .collect::<Vec<_>>(),
                 );
             }
 
@@ -290,6 +376,14 @@ impl UserPatchDriver {
 
         self.add_patch_target(patch);
         self.set_patch_status(&patch.uuid, PatchStatus::Deactived);
+        self.patch_records.insert(
+            patch.uuid,
+            PatchRecord {
+                target_elf: patch.target_elf.clone(),
+                patch_file: patch.patch_file.clone(),
+                functions: patch.functions.clone(),
+            },
+        );
 
         Ok(())
     }
@@ -303,17 +397,25 @@ impl UserPatchDriver {
 
         self.remove_patch_target(patch);
         self.remove_patch_status(&patch.uuid);
+        self.patch_records.remove(&patch.uuid);
 
         Ok(())
     }
 
     pub fn active(&mut self, patch: &UserPatch) -> Result<()> {
+        let conflicts = self.check_conflict_functions(patch)?;
+        ensure!(conflicts.is_good(), "{}", conflicts);
+
+        let overrides = self.check_override_functions(patch)?;
+        ensure!(overrides.is_good(), "{}", overrides);
+
         let patch_uuid = &patch.uuid;
         let patch_file = patch.patch_file.as_path();
         let patch_functions = patch.functions.as_slice();
         let target_elf = patch.target_elf.as_path();
 
-        let process_list = Self::find_target_process(target_elf)?;
+        let inventory = ProcessInventory::collect();
+        let process_list = Self::find_target_process_in(&inventory, target_elf);
 
         let mut target_map = self.target_map.write();
         let patch_target = target_map
@@ -334,8 +436,13 @@ impl UserPatchDriver {
         let mut results = Vec::new();
         for pid in patch_entity.need_actived(&process_list) {
             let result = sys::active_patch(patch_uuid, pid, target_elf, patch_file);
-            match result {
-                Ok(_) => patch_entity.add_process(pid),
+            match &result {
+                Ok(_) => {
+                    patch_entity.add_process(pid);
+                    if let Some(process) = inventory.get(pid) {
+                        debug!("Upatch: Activated patch '{}' for {}", patch_uuid, process.describe());
+                    }
+                }
                 Err(_) => patch_entity.ignore_process(pid),
             }
             results.push((pid, result));
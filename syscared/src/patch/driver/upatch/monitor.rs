@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: Mulan PSL v2
+/*
+ * Copyright (c) 2024 Huawei Technologies Co., Ltd.
+ * syscared is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the Mulan PSL v2.
+ * You may obtain a copy of Mulan PSL v2 at:
+ *         http://license.coscl.org.cn/MulanPSL2
+ *
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+ * EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+ * MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ */
+
+use std::{
+    collections::HashMap,
+    os::linux::fs::MetadataExt,
+    path::{Path, PathBuf},
+    sync::{mpsc::channel, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use glob::Pattern;
+use indexmap::IndexMap;
+use log::{debug, warn};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::RwLock;
+
+use super::target::PatchTarget;
+
+/// Default quiet window used to coalesce the burst of create/rename/write events that
+/// an RPM/dpkg upgrade produces into a single settled event.
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Transient writes near a watched path that should never trigger a re-patch attempt,
+/// e.g. editor swap files or compiled Python caches left behind by a package post-script.
+const DEFAULT_IGNORE_PATTERNS: &[&str] = &[".*.sw?", "#*#", "*.py[co]"];
+
+type PatchCallback = fn(Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>, &Path);
+
+struct WatchedTarget {
+    inode: u64,
+    last_event: Instant,
+}
+
+/// Watches every userspace patch target for in-place binary replacement (a package
+/// upgrade that keeps the path but swaps the inode) and re-drives `patch_new_process`
+/// once the upgrade's burst of filesystem events has settled.
+pub struct UserPatchMonitor {
+    target_map: Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>,
+    on_replace: PatchCallback,
+    debounce: Duration,
+    ignore_patterns: Vec<Pattern>,
+    watcher: RecommendedWatcher,
+    watched: Arc<RwLock<HashMap<PathBuf, WatchedTarget>>>,
+}
+
+impl UserPatchMonitor {
+    pub fn new(
+        target_map: Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>,
+        on_replace: PatchCallback,
+    ) -> Result<Self> {
+        Self::with_options(target_map, on_replace, DEFAULT_DEBOUNCE, DEFAULT_IGNORE_PATTERNS)
+    }
+
+    /// Same as `new`, but lets the caller override the debounce window and the set of
+    /// glob patterns used to ignore transient, non-upgrade writes.
+    pub fn with_options(
+        target_map: Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>,
+        on_replace: PatchCallback,
+        debounce: Duration,
+        ignore_patterns: &[&str],
+    ) -> Result<Self> {
+        let ignore_patterns = ignore_patterns
+            .iter()
+            .map(|pattern| Pattern::new(pattern).context("Upatch: Invalid ignore pattern"))
+            .collect::<Result<Vec<_>>>()?;
+
+        let watched = Arc::new(RwLock::new(HashMap::new()));
+        let (event_tx, event_rx) = channel();
+        let watcher = notify::recommended_watcher(event_tx)
+            .context("Upatch: Failed to create file watcher")?;
+
+        let instance = Self {
+            target_map,
+            on_replace,
+            debounce,
+            ignore_patterns,
+            watcher,
+            watched,
+        };
+
+        let target_map = instance.target_map.clone();
+        let watched = instance.watched.clone();
+        let on_replace = instance.on_replace;
+        let debounce = instance.debounce;
+        let ignore_patterns = instance.ignore_patterns.clone();
+
+        thread::Builder::new()
+            .name("upatch-monitor".to_string())
+            .spawn(move || {
+                Self::event_loop(event_rx, target_map, watched, on_replace, debounce, &ignore_patterns)
+            })
+            .context("Upatch: Failed to start file monitor thread")?;
+
+        Ok(instance)
+    }
+
+    fn is_ignored(ignore_patterns: &[Pattern], path: &Path) -> bool {
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => return false,
+        };
+        ignore_patterns.iter().any(|pattern| pattern.matches(file_name))
+    }
+
+    fn event_loop(
+        event_rx: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+        target_map: Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>,
+        watched: Arc<RwLock<HashMap<PathBuf, WatchedTarget>>>,
+        on_replace: PatchCallback,
+        debounce: Duration,
+        ignore_patterns: &[Pattern],
+    ) {
+        loop {
+            let event = match event_rx.recv_timeout(debounce) {
+                Ok(Ok(event)) => event,
+                Ok(Err(e)) => {
+                    warn!("Upatch: File monitor error, {}", e.to_string().to_lowercase());
+                    continue;
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    Self::settle_due_targets(&target_map, &watched, on_replace, debounce);
+                    continue;
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+
+            for path in event.paths {
+                if Self::is_ignored(ignore_patterns, &path) {
+                    continue;
+                }
+                let mut watched = watched.write();
+                if let Some(target) = watched.get_mut(&path) {
+                    target.last_event = Instant::now();
+                }
+            }
+        }
+    }
+
+    /// Re-check every watched target whose debounce window has elapsed since its last
+    /// event, and re-drive `patch_new_process` for any whose inode changed underneath it.
+    fn settle_due_targets(
+        target_map: &Arc<RwLock<IndexMap<PathBuf, PatchTarget>>>,
+        watched: &Arc<RwLock<HashMap<PathBuf, WatchedTarget>>>,
+        on_replace: PatchCallback,
+        debounce: Duration,
+    ) {
+        let due_targets: Vec<PathBuf> = watched
+            .read()
+            .iter()
+            .filter(|(_, target)| target.last_event.elapsed() >= debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for target_path in due_targets {
+            let current_inode = match target_path.metadata() {
+                Ok(metadata) => metadata.st_ino(),
+                Err(_) => continue,
+            };
+
+            let mut watched = watched.write();
+            let entry = match watched.get_mut(&target_path) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            // Nothing changed since the last settle pass, skip re-patching.
+            if entry.inode == current_inode {
+                continue;
+            }
+
+            debug!(
+                "Upatch: Target '{}' was replaced (inode {} -> {}), re-patching new processes",
+                target_path.display(),
+                entry.inode,
+                current_inode,
+            );
+            entry.inode = current_inode;
+            drop(watched);
+
+            on_replace(target_map.clone(), &target_path);
+        }
+    }
+
+    pub fn watch_file<P: AsRef<Path>>(&mut self, target_elf: P) -> Result<()> {
+        let target_path = target_elf.as_ref();
+        let watch_dir = target_path
+            .parent()
+            .context("Upatch: Target has no parent directory")?;
+
+        self.watcher
+            .watch(watch_dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Upatch: Failed to watch '{}'", watch_dir.display()))?;
+
+        let inode = target_path.metadata()?.st_ino();
+        self.watched.write().insert(
+            target_path.to_path_buf(),
+            WatchedTarget {
+                inode,
+                last_event: Instant::now(),
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn ignore_file<P: AsRef<Path>>(&mut self, target_elf: P) -> Result<()> {
+        let target_path = target_elf.as_ref();
+        self.watched.write().remove(target_path);
+
+        // Only stop watching the parent directory once no other target inside it
+        // is still being monitored.
+        if let Some(watch_dir) = target_path.parent() {
+            let still_needed = self
+                .watched
+                .read()
+                .keys()
+                .any(|path| path.parent() == Some(watch_dir));
+            if !still_needed {
+                let _ = self.watcher.unwatch(watch_dir);
+            }
+        }
+
+        Ok(())
+    }
+}
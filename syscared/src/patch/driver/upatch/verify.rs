@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: Mulan PSL v2
+/*
+ * Copyright (c) 2024 Huawei Technologies Co., Ltd.
+ * syscared is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the Mulan PSL v2.
+ * You may obtain a copy of Mulan PSL v2 at:
+ *         http://license.coscl.org.cn/MulanPSL2
+ *
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+ * EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+ * MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ */
+
+use std::fmt::{self, Display};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Why a function symbol was flagged during `check_conflict_functions`/
+/// `check_override_functions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyKind {
+    /// Another, currently-active patch touches the same function.
+    Conflict,
+    /// The function is already patched by a newer patch on the same target.
+    Override,
+}
+
+/// A single conflicting/overriding function symbol found for one patch.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyRecord {
+    pub conflicting_uuid: Uuid,
+    pub function_symbol: String,
+    pub kind: VerifyKind,
+}
+
+/// Structured result of `check_conflict_functions`/`check_override_functions`, so callers
+/// can render it as text (the original behavior) or serialize it for tooling. `active`
+/// renders a non-empty result into its error message; no caller maps conflict and override
+/// findings to different exit codes.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct PatchVerifyResult {
+    pub records: Vec<VerifyRecord>,
+}
+
+impl PatchVerifyResult {
+    pub fn new(records: Vec<VerifyRecord>) -> Self {
+        Self { records }
+    }
+
+    /// No conflicting/overriding function symbols were found.
+    pub fn is_good(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl Display for PatchVerifyResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_good() {
+            return Ok(());
+        }
+
+        let verb = match self.records[0].kind {
+            VerifyKind::Conflict => "conflicted with",
+            VerifyKind::Override => "overrided by",
+        };
+
+        writeln!(f, "Upatch: Patch is {}", verb)?;
+        for (i, record) in self.records.iter().enumerate() {
+            if i + 1 == self.records.len() {
+                write!(
+                    f,
+                    "* Patch '{}' (function '{}')",
+                    record.conflicting_uuid, record.function_symbol
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "* Patch '{}' (function '{}')",
+                    record.conflicting_uuid, record.function_symbol
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: Mulan PSL v2
+/*
+ * Copyright (c) 2024 Huawei Technologies Co., Ltd.
+ * syscared is licensed under Mulan PSL v2.
+ * You can use this software according to the terms and conditions of the Mulan PSL v2.
+ * You may obtain a copy of Mulan PSL v2 at:
+ *         http://license.coscl.org.cn/MulanPSL2
+ *
+ * THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+ * EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+ * MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+ * See the Mulan PSL v2 for more details.
+ */
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+};
+
+use anyhow::{Context, Result};
+
+use super::super::KernelPatchSymbol;
+
+const KALLSYMS_PATH: &str = "/proc/kallsyms";
+const VMLINUX_TARGET: &str = "vmlinux";
+
+/// Why a patch symbol failed pre-activation validation against the running kernel.
+#[derive(Debug, Clone)]
+pub enum SymbolIssueKind {
+    /// No symbol with this name exists in the target object's live symbol table.
+    Missing,
+    /// The symbol exists, but its estimated size (the gap to the next symbol address in the
+    /// same object) disagrees with the size the patch was built against.
+    SizeMismatch { expected: u64, actual: u64 },
+    /// The name is not unique within the target object and the patch's `sympos` does not
+    /// select exactly one of the candidates.
+    AmbiguousPosition { candidates: usize },
+}
+
+/// One patch symbol that failed validation, reported so operators can see the full picture
+/// instead of failing on the first mismatch.
+#[derive(Debug, Clone)]
+pub struct SymbolIssue {
+    pub name: String,
+    pub target: String,
+    pub kind: SymbolIssueKind,
+}
+
+impl Display for SymbolIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            SymbolIssueKind::Missing => write!(
+                f,
+                "symbol '{}' not found in '{}'",
+                self.name, self.target
+            ),
+            SymbolIssueKind::SizeMismatch { expected, actual } => write!(
+                f,
+                "symbol '{}' in '{}' size mismatch (expected {:#x}, running kernel has {:#x})",
+                self.name, self.target, expected, actual
+            ),
+            SymbolIssueKind::AmbiguousPosition { candidates } => write!(
+                f,
+                "symbol '{}' in '{}' is ambiguous ({} candidates, sympos does not disambiguate)",
+                self.name, self.target, candidates
+            ),
+        }
+    }
+}
+
+/// A single `/proc/kallsyms` entry: address, symbol name, and owning module (`None` for
+/// symbols built into `vmlinux`).
+struct KallsymsEntry {
+    address: u64,
+    name: String,
+    module: Option<String>,
+}
+
+/// Reads and parses `/proc/kallsyms`. Each line is `address type name [module]`; the
+/// module column is only present for symbols contributed by a loaded kernel module.
+fn read_kallsyms() -> Result<Vec<KallsymsEntry>> {
+    let text = fs::read_to_string(KALLSYMS_PATH)
+        .with_context(|| format!("Failed to read \"{}\"", KALLSYMS_PATH))?;
+
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let address = fields.next();
+        let _kind = fields.next();
+        let name = fields.next();
+        let (address, name) = match (address, name) {
+            (Some(address), Some(name)) => (address, name),
+            _ => continue,
+        };
+        let module = fields
+            .next()
+            .map(|module| module.trim_start_matches('[').trim_end_matches(']').to_owned());
+
+        if let Ok(address) = u64::from_str_radix(address, 16) {
+            entries.push(KallsymsEntry {
+                address,
+                name: name.to_owned(),
+                module,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Validates `symbols` against the running kernel's `/proc/kallsyms`, returning one
+/// [`SymbolIssue`] per symbol that is missing, mismatched in size, or ambiguous.
+///
+/// KASLR shifts every address within an object (`vmlinux` or a module) by the same random
+/// slide, so absolute addresses are never compared; only the ordering of same-named
+/// candidates within an object (used for `sympos` disambiguation) and size, which is
+/// KASLR-invariant, are checked.
+pub fn validate_symbols(symbols: &[KernelPatchSymbol]) -> Result<Vec<SymbolIssue>> {
+    let kallsyms = read_kallsyms()?;
+
+    // Group live symbols by owning object, sorted by address, so a symbol's size can be
+    // approximated from the gap to the next address in the same object (kallsyms carries no
+    // size column).
+    let mut by_object: HashMap<&str, Vec<&KallsymsEntry>> = HashMap::new();
+    for entry in &kallsyms {
+        let object = entry.module.as_deref().unwrap_or(VMLINUX_TARGET);
+        by_object.entry(object).or_default().push(entry);
+    }
+    for entries in by_object.values_mut() {
+        entries.sort_by_key(|entry| entry.address);
+    }
+
+    let mut issues = Vec::new();
+    for symbol in symbols {
+        let name = symbol.name.to_string_lossy();
+        let target = symbol.target.to_string_lossy();
+
+        let object_entries = match by_object.get(target.as_ref()) {
+            Some(entries) => entries,
+            None => {
+                issues.push(SymbolIssue {
+                    name: name.into_owned(),
+                    target: target.into_owned(),
+                    kind: SymbolIssueKind::Missing,
+                });
+                continue;
+            }
+        };
+
+        let candidate_indices: Vec<usize> = object_entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.name == name)
+            .map(|(index, _)| index)
+            .collect();
+
+        if candidate_indices.is_empty() {
+            issues.push(SymbolIssue {
+                name: name.into_owned(),
+                target: target.into_owned(),
+                kind: SymbolIssueKind::Missing,
+            });
+            continue;
+        }
+
+        let selected = match candidate_indices.len() {
+            1 => candidate_indices[0],
+            candidates => match symbol.sympos {
+                0 => {
+                    issues.push(SymbolIssue {
+                        name: name.into_owned(),
+                        target: target.into_owned(),
+                        kind: SymbolIssueKind::AmbiguousPosition { candidates },
+                    });
+                    continue;
+                }
+                sympos => match candidate_indices.get((sympos - 1) as usize) {
+                    Some(&index) => index,
+                    None => {
+                        issues.push(SymbolIssue {
+                            name: name.into_owned(),
+                            target: target.into_owned(),
+                            kind: SymbolIssueKind::AmbiguousPosition { candidates },
+                        });
+                        continue;
+                    }
+                },
+            },
+        };
+
+        let matched = object_entries[selected];
+        let estimated_size = object_entries
+            .get(selected + 1)
+            .map(|next| next.address.saturating_sub(matched.address))
+            .unwrap_or(0);
+
+        if estimated_size != 0 && estimated_size != symbol.old_size {
+            issues.push(SymbolIssue {
+                name: name.into_owned(),
+                target: target.into_owned(),
+                kind: SymbolIssueKind::SizeMismatch {
+                    expected: symbol.old_size,
+                    actual: estimated_size,
+                },
+            });
+        }
+    }
+
+    Ok(issues)
+}
@@ -34,6 +34,7 @@ use syscare_common::{
         os_str::OsStringExt,
     },
 };
+mod kallsyms;
 mod target;
 use target::PatchTarget;
 
@@ -220,6 +221,33 @@ impl KernelPatchDriver {
         Ok(())
     }
 
+    /// Cross-checks the patch's `KernelPatchSymbol` list against `/proc/kallsyms` of the
+    /// running kernel before activation, so a build mismatched against this kernel is
+    /// rejected here instead of silently failing inside livepatch.
+    fn check_symbol_compatibility(patch: &Patch) -> Result<()> {
+        let patch_ext: &KernelPatchExt = (&patch.info_ext).into();
+        let issues = kallsyms::validate_symbols(&patch_ext.symbols)
+            .context("Kpatch: Failed to validate patch symbols against the running kernel")?;
+
+        ensure!(issues.is_empty(), {
+            let mut err_msg = String::new();
+
+            writeln!(
+                &mut err_msg,
+                "Kpatch: Patch \"{}\" is incompatible with the running kernel",
+                patch
+            )?;
+            for issue in &issues {
+                writeln!(&mut err_msg, "* {}", issue)?;
+            }
+            err_msg.pop();
+
+            err_msg
+        });
+
+        Ok(())
+    }
+
     fn add_patch_symbols(&mut self, patch: &Patch) {
         let patch_ext: &KernelPatchExt = (&patch.info_ext).into();
         let symbols = patch_ext.symbols.to_owned();
@@ -354,9 +382,10 @@ impl PatchDriver for KernelPatchDriver {
         Ok(())
     }
 
-    fn active(&mut self, patch: &Patch, _flag: PatchOpFlag) -> Result<()> {
+    fn active(&mut self, patch: &Patch, flag: PatchOpFlag) -> Result<()> {
         if flag != PatchOpFlag::Force {
             self.check_conflict_symbols(patch)?;
+            Self::check_symbol_compatibility(patch)?;
         }
         Self::set_patch_status(patch, PatchStatus::Actived)?;
       
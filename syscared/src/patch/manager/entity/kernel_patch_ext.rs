@@ -94,25 +94,63 @@ const KPATCH_FUNCS_SECTION: &str = ".kpatch.funcs";
 const KPATCH_STRINGS_SECTION: &str = ".kpatch.strings";
 
 impl KernelPatchExt {
+    /// Reads a `\0`-terminated string out of `.kpatch.strings` at `offset`, naming the patch
+    /// file and offset in the error so a corrupt string table doesn't just say "not found".
+    fn read_patch_string(patch_file: &Path, string_data: &[u8], offset: usize) -> Result<OsString> {
+        let name_bytes = string_data.get(offset..).with_context(|| {
+            format!(
+                "Patch file '{}': string offset {:#x} is out of range of '{}' ({} bytes)",
+                patch_file.display(),
+                offset,
+                KPATCH_STRINGS_SECTION,
+                string_data.len()
+            )
+        })?;
+        let string_end = name_bytes.iter().position(|b| b == &b'\0').with_context(|| {
+            format!(
+                "Patch file '{}': string at offset {:#x} in '{}' is not null-terminated",
+                patch_file.display(),
+                offset,
+                KPATCH_STRINGS_SECTION
+            )
+        })?;
+
+        Ok(OsString::from_vec(name_bytes[..string_end].to_vec()))
+    }
+
     #[inline]
     fn resolve_patch_file(patch: &mut KernelPatchExt) -> Result<()> {
+        let patch_file_path = patch.patch_file.clone();
+        let context = || format!("Patch file '{}'", patch_file_path.display());
+
         let patch_file =
-            fs::MappedFile::open(&patch.patch_file).context("Failed to map patch file")?;
-        let patch_elf = NativeFile::parse(patch_file.as_bytes()).context("Invalid patch format")?;
+            fs::MappedFile::open(&patch.patch_file).with_context(|| format!("{}: failed to map file", context()))?;
+        let patch_elf =
+            NativeFile::parse(patch_file.as_bytes()).with_context(|| format!("{}: invalid ELF format", context()))?;
 
         // Read sections
         let function_section = patch_elf
             .section_by_name(KPATCH_FUNCS_SECTION)
-            .with_context(|| format!("Cannot find section '{}'", KPATCH_FUNCS_SECTION))?;
+            .with_context(|| format!("{}: cannot find section '{}'", context(), KPATCH_FUNCS_SECTION))?;
         let string_section = patch_elf
             .section_by_name(KPATCH_STRINGS_SECTION)
-            .with_context(|| format!("Cannot find section '{}'", KPATCH_STRINGS_SECTION))?;
+            .with_context(|| format!("{}: cannot find section '{}'", context(), KPATCH_STRINGS_SECTION))?;
         let function_data = function_section
             .data()
-            .with_context(|| format!("Failed to read section '{}'", KPATCH_FUNCS_SECTION))?;
+            .with_context(|| format!("{}: failed to read section '{}'", context(), KPATCH_FUNCS_SECTION))?;
         let string_data = string_section
             .data()
-            .with_context(|| format!("Failed to read section '{}'", KPATCH_FUNCS_SECTION))?;
+            .with_context(|| format!("{}: failed to read section '{}'", context(), KPATCH_STRINGS_SECTION))?;
+
+        if (function_data.len() % KPATCH_FUNC_SIZE) != 0 {
+            return Err(anyhow!(
+                "{}: section '{}' size {} is not a multiple of kpatch_patch_func size {}",
+                context(),
+                KPATCH_FUNCS_SECTION,
+                function_data.len(),
+                KPATCH_FUNC_SIZE
+            ));
+        }
 
         // Resolve patch functions
         let patch_symbols = &mut patch.symbols;
@@ -121,13 +159,13 @@ impl KernelPatchExt {
             function_data.len() / KPATCH_FUNC_SIZE,
         )
         .map(|(f, _)| f)
-        .map_err(|_| anyhow!("Invalid data format"))
-        .context("Failed to resolve patch functions")?;
+        .map_err(|_| anyhow!("{}: failed to resolve '{}' as kpatch_patch_func entries", context(), KPATCH_FUNCS_SECTION))?;
 
         for function in patch_functions {
             patch_symbols.push(KernelPatchSymbol {
                 name: OsString::new(),
                 target: OsString::new(),
+                sympos: function.sympos,
                 old_addr: function.old_addr,
                 old_size: function.old_size,
                 new_addr: function.new_addr,
@@ -141,36 +179,36 @@ impl KernelPatchExt {
                 KpatchRelocation::Name => {
                     let symbol_index =
                         (offset as usize - KPATCH_FUNC_NAME_OFFSET) / KPATCH_FUNC_SIZE;
-                    let patch_symbol = patch_symbols
-                        .get_mut(symbol_index)
-                        .context("Failed to find patch symbol")?;
+                    let patch_symbol = patch_symbols.get_mut(symbol_index).with_context(|| {
+                        format!(
+                            "{}: relocation {} references out-of-range symbol {}",
+                            context(),
+                            index,
+                            symbol_index
+                        )
+                    })?;
 
                     let name_offset = relocation.addend() as usize;
-                    let mut name_bytes = &string_data[name_offset..];
-                    let string_end = name_bytes
-                        .iter()
-                        .position(|b| b == &b'\0')
-                        .context("Failed to find termination char")?;
-                    name_bytes = &name_bytes[..string_end];
-
-                    patch_symbol.name = OsString::from_vec(name_bytes.to_vec());
+                    patch_symbol.name =
+                        Self::read_patch_string(&patch_file_path, string_data, name_offset)
+                            .with_context(|| format!("Failed to resolve symbol {} name", symbol_index))?;
                 }
                 KpatchRelocation::ObjName => {
                     let symbol_index =
                         (offset as usize - KPATCH_OBJECT_NAME_OFFSET) / KPATCH_FUNC_SIZE;
-                    let patch_symbol = patch_symbols
-                        .get_mut(symbol_index)
-                        .context("Failed to find patch symbol")?;
+                    let patch_symbol = patch_symbols.get_mut(symbol_index).with_context(|| {
+                        format!(
+                            "{}: relocation {} references out-of-range symbol {}",
+                            context(),
+                            index,
+                            symbol_index
+                        )
+                    })?;
 
                     let name_offset = relocation.addend() as usize;
-                    let mut name_bytes = &string_data[name_offset..];
-                    let string_end = name_bytes
-                        .iter()
-                        .position(|b| b == &b'\0')
-                        .context("Failed to find termination char")?;
-                    name_bytes = &name_bytes[..string_end];
-
-                    patch_symbol.target = OsString::from_vec(name_bytes.to_vec());
+                    patch_symbol.target =
+                        Self::read_patch_string(&patch_file_path, string_data, name_offset)
+                            .with_context(|| format!("Failed to resolve symbol {} target", symbol_index))?;
                 }
                 _ => {}
             };
@@ -183,7 +221,7 @@ impl KernelPatchExt {
 
 
 impl KernelPatchExt {
-    pub fn new<P: AsRef<Path>>(patch_root: P, patch_entity: &PatchEntity) -> Self {
+    pub fn new<P: AsRef<Path>>(patch_root: P, patch_entity: &PatchEntity) -> Result<Self> {
         const KPATCH_SUFFIX: &str = ".ko";
         const KPATCH_MGNT_DIR: &str = "/sys/kernel/livepatch";
         const KPATCH_MGNT_FILE_NAME: &str = "enabled";
@@ -196,20 +234,18 @@ impl KernelPatchExt {
         let patch_sys_name = patch_name.replace('-', "_").replace('.', "_");
         let patch_file_name = format!("{}{}", patch_name, KPATCH_SUFFIX);
         let module_name = patch_sys_name.to_owned();
-      //  let symbols :Vec<KernelPatchSymbol> =Vec::new();
-
 
-        let mut kernel_patch_ext = KernelPatchExt  {
+        let mut kernel_patch_ext = KernelPatchExt {
             patch_file: patch_root.as_ref().join(patch_file_name),
             sys_file: PathBuf::from(KPATCH_MGNT_DIR)
                 .join(patch_sys_name)
                 .join(KPATCH_MGNT_FILE_NAME),
-            module_name:module_name,
-            symbols:Vec::new(),
+            module_name,
+            symbols: Vec::new(),
         };
-        Self::resolve_patch_file(&mut kernel_patch_ext).context("Failed to resolve patch elf").unwrap();
-        
-       kernel_patch_ext
+        Self::resolve_patch_file(&mut kernel_patch_ext).context("Failed to resolve patch elf")?;
+
+        Ok(kernel_patch_ext)
     }
 }
 
@@ -229,6 +265,9 @@ impl<'a> From<&'a PatchInfoExt> for &'a KernelPatchExt {
 pub struct KernelPatchSymbol {
     pub name: OsString,
     pub target: OsString,
+    /// 1-based ordinal of this symbol among same-named symbols within `target`, ordered by
+    /// address, as recorded by `kpatch-build`; `0` means the name is unique within `target`.
+    pub sympos: u64,
     pub old_addr: u64,
     pub old_size: u64,
     pub new_addr: u64,
@@ -240,6 +279,7 @@ impl std::fmt::Debug for KernelPatchSymbol {
         f.debug_struct("KernelPatchSymbol")
             .field("name", &self.name)
             .field("target", &self.target)
+            .field("sympos", &self.sympos)
             .field("old_addr", &format!("{:#x}", self.old_addr))
             .field("old_size", &format!("{:#x}", self.old_size))
             .field("new_addr", &format!("{:#x}", self.new_addr))
@@ -252,9 +292,10 @@ impl std::fmt::Display for KernelPatchSymbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
              f,
-             "name: {}, target: {}, old_addr: {:#x}, old_size: {:#x}, new_addr: {:#x}, new_size: {:#x}",
+             "name: {}, target: {}, sympos: {}, old_addr: {:#x}, old_size: {:#x}, new_addr: {:#x}, new_size: {:#x}",
              self.name.to_string_lossy(),
              self.target.to_string_lossy(),
+             self.sympos,
              self.old_addr,
              self.old_size,
              self.new_addr,
@@ -1,5 +1,7 @@
 use std::borrow::{Cow, Borrow};
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::io;
 use std::path::{Path, PathBuf};
 use std::ffi::{OsString, OsStr};
@@ -16,11 +18,106 @@ use crate::tool::*;
 
 type RelocationMap = HashMap<usize, object::Relocation>;
 
+/// Minimal `/proc/self/mountinfo` reader, used only to tell whether a given path lives on a
+/// network filesystem where mmap-ing a file is unsafe (a truncated or disconnected backing
+/// file raises an uncatchable `SIGBUS`).
+mod mount {
+    use std::ffi::OsString;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Filesystem types this build tool treats as "do not mmap". `fuse.*` is matched by
+    /// prefix since FUSE backends register themselves as `fuse.<name>`.
+    const NETWORK_FILESYSTEMS: &[&str] = &["nfs", "nfs4", "cifs", "smb3", "9p"];
+
+    pub struct MountRecord {
+        pub target: PathBuf,
+        pub filesystem: OsString,
+    }
+
+    pub struct Mounts {
+        records: Vec<MountRecord>,
+    }
+
+    impl Mounts {
+        pub fn load() -> std::io::Result<Self> {
+            let text = fs::read_to_string("/proc/self/mountinfo")?;
+
+            let mut records = Vec::new();
+            for line in text.lines() {
+                // Format: "<id> <parent-id> <major:minor> <root> <mount-point> <options>
+                // <optional-fields...> - <fs-type> <source> <super-options>". The optional
+                // fields before the literal "-" separator are 0 or more, so their count (and
+                // therefore the total field count) varies between lines; only the fields'
+                // positions relative to "-" are fixed.
+                let fields = line.split(' ').collect::<Vec<_>>();
+                let separator_idx = match fields.iter().position(|&field| field == "-") {
+                    Some(idx) => idx,
+                    None => continue,
+                };
+                let filesystem = match fields.get(separator_idx + 1) {
+                    Some(filesystem) => filesystem,
+                    None => continue,
+                };
+                let target = match fields.get(4) {
+                    Some(target) => target,
+                    None => continue,
+                };
+
+                records.push(MountRecord {
+                    target: PathBuf::from(*target),
+                    filesystem: OsString::from(*filesystem),
+                });
+            }
+
+            Ok(Self { records })
+        }
+
+        /// Returns the mount record whose `target` is the longest path-prefix of `path`,
+        /// i.e. the mount point `path` actually resides under.
+        pub fn find_mount_of(&self, path: &Path) -> Option<&MountRecord> {
+            self.records
+                .iter()
+                .filter(|record| path.starts_with(&record.target))
+                .max_by_key(|record| record.target.as_os_str().len())
+        }
+    }
+
+    pub fn is_network_filesystem(filesystem: &OsString) -> bool {
+        let filesystem = filesystem.to_string_lossy();
+        NETWORK_FILESYSTEMS.contains(&filesystem.as_ref()) || filesystem.starts_with("fuse.")
+    }
+}
+
+use mount::Mounts;
+
+/// Either an mmap of the ELF file (the fast, common path) or its contents read into memory
+/// (used on network filesystems, where mmap can raise an uncatchable `SIGBUS` if the backing
+/// file is truncated or the server disappears mid-parse).
+enum ElfData {
+    Mapped(memmap2::Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl std::ops::Deref for ElfData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ElfData::Mapped(mmap) => mmap,
+            ElfData::Buffered(buf) => buf,
+        }
+    }
+}
+
 #[allow(non_snake_case)]
 pub struct DwarfCompileUnit {
     pub DW_AT_producer: String,
     pub DW_AT_comp_dir: PathBuf,
     pub DW_AT_name: PathBuf,
+    /// `(name, low_pc, high_pc)` of every `DW_TAG_subprogram` DIE found in this compile unit
+    /// that carries both a name and a `DW_AT_low_pc` address.
+    pub functions: Vec<(String, u64, u64)>,
 }
 
 impl DwarfCompileUnit{
@@ -29,6 +126,7 @@ impl DwarfCompileUnit{
             DW_AT_producer: String::new(),
             DW_AT_comp_dir: PathBuf::new(),
             DW_AT_name: PathBuf::new(),
+            functions: Vec::new(),
         }
     }
 
@@ -54,11 +152,9 @@ impl Dwarf{
     }
 
     pub fn file_in_obj<P: AsRef<Path>>(&self, elf: P) -> io::Result<Vec<DwarfCompileUnit>> {
-        // use mmap here, but depend on some devices
         let elf = elf.as_ref();
-        let file = std::fs::File::open(elf)?;
-        let mmap = unsafe { memmap2::Mmap::map(&file)? };
-        match object::File::parse(&*mmap) {
+        let data = self.read_elf_data(elf)?;
+        match object::File::parse(&*data) {
             Ok(object) => {
                 let endian = if object.is_little_endian() {
                     gimli::RunTimeEndian::Little
@@ -73,6 +169,91 @@ impl Dwarf{
             Err(e) => Err(io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
         }
     }
+
+    /// Computes a content signature for every defined function symbol in `elf`, keyed by
+    /// symbol name. Each signature hashes the symbol's bytes within its containing section
+    /// with relocated bytes masked out, so two objects that differ only by relocation
+    /// addends (as happens when the same source is rebuilt in a different directory) hash
+    /// identically. Used as a fallback correlation key when `DW_AT_comp_dir` doesn't agree
+    /// between the source and patched builds.
+    pub fn symbol_signatures<P: AsRef<Path>>(&self, elf: P) -> io::Result<HashMap<String, u64>> {
+        let elf = elf.as_ref();
+        let data = self.read_elf_data(elf)?;
+        let file = object::File::parse(&*data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+        let mut signatures = HashMap::new();
+        for symbol in file.symbols() {
+            if !symbol.is_definition() || symbol.kind() != object::SymbolKind::Text {
+                continue;
+            }
+            let section = match symbol.section_index().and_then(|index| file.section_by_index(index).ok()) {
+                Some(section) => section,
+                None => continue,
+            };
+            let section_data = match section.uncompressed_data() {
+                Ok(data) => data,
+                Err(_) => continue,
+            };
+
+            let start = symbol.address().saturating_sub(section.address()) as usize;
+            let end = start.saturating_add(symbol.size() as usize).min(section_data.len());
+            if start >= end {
+                continue;
+            }
+
+            let mut relocations = RelocationMap::default();
+            self.add_relocations(&mut relocations, &file, &section);
+
+            // Mask relocated bytes so differing relative addresses don't change the hash.
+            // 8 bytes covers the widest relocation width (absolute 64-bit) we handle.
+            let mut bytes = section_data[start..end].to_vec();
+            for offset in relocations.keys() {
+                if *offset < start || *offset >= end {
+                    continue;
+                }
+                for byte in bytes[(offset - start)..].iter_mut().take(8) {
+                    *byte = 0;
+                }
+            }
+
+            let mut hasher = DefaultHasher::new();
+            bytes.hash(&mut hasher);
+
+            if let Ok(name) = symbol.name() {
+                signatures.insert(name.to_owned(), hasher.finish());
+            }
+        }
+
+        Ok(signatures)
+    }
+}
+
+impl Dwarf {
+    /// Reads an ELF file for DWARF parsing, using mmap on local filesystems (the fast path)
+    /// and a plain buffered read on network/remote filesystems (see [`mount::is_network_filesystem`]).
+    fn read_elf_data(&self, elf: &Path) -> io::Result<ElfData> {
+        if self.is_network_path(elf) {
+            return Ok(ElfData::Buffered(std::fs::read(elf)?));
+        }
+
+        let file = std::fs::File::open(elf)?;
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(ElfData::Mapped(mmap))
+    }
+
+    fn is_network_path(&self, elf: &Path) -> bool {
+        let canonical = std::fs::canonicalize(elf).unwrap_or_else(|_| elf.to_path_buf());
+
+        Mounts::load()
+            .ok()
+            .and_then(|mounts| {
+                mounts
+                    .find_mount_of(&canonical)
+                    .map(|record| mount::is_network_filesystem(&record.filesystem))
+            })
+            .unwrap_or(false)
+    }
 }
 
 impl Dwarf {
@@ -177,33 +358,110 @@ impl Dwarf {
         while let Some(header) = iter.next()? {
             let unit = dwarf.unit(header)?;
             let mut entries = unit.entries();
+            let mut element = DwarfCompileUnit::new();
+            let mut found_compile_unit = false;
+
+            // Walk the whole DIE tree of this unit (not just its root), so functions nested
+            // under the compile_unit DIE are collected alongside it.
             while let Some((_, entry)) = entries.next_dfs()? {
-                if entry.tag() != constants::DW_TAG_compile_unit{
-                    break;
-                }
-                // Iterate over the attributes in the DIE.
-                let mut attrs = entry.attrs();
-                let mut element = DwarfCompileUnit::new();
-                while let Some(attr) = attrs.next()? {
-                    match attr.name() {
-                        constants::DW_AT_comp_dir => {
-                            element.DW_AT_comp_dir.push(&self.attr_value(&attr, &dwarf));
-                        },
-                        constants::DW_AT_name => {
-                            element.DW_AT_name.push(&self.attr_value(&attr, &dwarf));
-                        },
-                        constants::DW_AT_producer => {
-                            element.DW_AT_producer.push_str(&self.attr_value(&attr, &dwarf).to_string_lossy());
+                match entry.tag() {
+                    constants::DW_TAG_compile_unit => {
+                        found_compile_unit = true;
+
+                        let mut dwo_name = None;
+                        let mut dwo_id = None;
+
+                        let mut attrs = entry.attrs();
+                        while let Some(attr) = attrs.next()? {
+                            match attr.name() {
+                                constants::DW_AT_comp_dir => {
+                                    element.DW_AT_comp_dir.push(&self.attr_value(&attr, &dwarf));
+                                },
+                                constants::DW_AT_name => {
+                                    element.DW_AT_name.push(&self.attr_value(&attr, &dwarf));
+                                },
+                                constants::DW_AT_producer => {
+                                    element.DW_AT_producer.push_str(&self.attr_value(&attr, &dwarf).to_string_lossy());
+                                }
+                                constants::DW_AT_GNU_dwo_name | constants::DW_AT_dwo_name => {
+                                    dwo_name = Some(self.attr_value(&attr, &dwarf));
+                                }
+                                constants::DW_AT_GNU_dwo_id | constants::DW_AT_dwo_id => {
+                                    dwo_id = attr.value().udata_value();
+                                }
+                                _ => continue,
+                            }
+                        }
+
+                        // A skeleton unit built with `-gsplit-dwarf` carries little beyond
+                        // `dwo_name`/`dwo_id`; resolve the companion `.dwo` (or `.dwp` package)
+                        // to fill in the attributes and functions it's missing.
+                        if let Some(dwo_name) = dwo_name {
+                            if let Err(e) = self.merge_dwo(&element.DW_AT_comp_dir, &dwo_name, dwo_id, &mut element) {
+                                trace!("Failed to resolve split DWARF companion {:?}: {}", dwo_name, e);
+                            }
+                        }
+                    }
+                    constants::DW_TAG_subprogram => {
+                        if let Some(function) = self.subprogram_function(entry, dwarf)? {
+                            element.functions.push(function);
                         }
-                        _ => continue,
                     }
+                    _ => continue,
                 }
+            }
+
+            if found_compile_unit {
                 result.push(element);
             }
         }
         Ok(result)
     }
 
+    /// Extracts `(name, low_pc, high_pc)` from a `DW_TAG_subprogram` DIE, if it carries both
+    /// a name and an address range. `DW_AT_high_pc` may be an absolute address or (commonly,
+    /// with DWARF4+) a `DW_FORM_data*` offset to be added to `low_pc`.
+    fn subprogram_function<R: Reader>(
+        &self,
+        entry: &gimli::DebuggingInformationEntry<R>,
+        dwarf: &gimli::Dwarf<R>,
+    ) -> Result<Option<(String, u64, u64)>> {
+        let mut name = None;
+        let mut low_pc = None;
+        let mut high_pc_value = None;
+
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_name => {
+                    name = Some(self.attr_value(&attr, dwarf).to_string_lossy().into_owned());
+                }
+                constants::DW_AT_low_pc => {
+                    if let gimli::AttributeValue::Addr(addr) = attr.value() {
+                        low_pc = Some(addr);
+                    }
+                }
+                constants::DW_AT_high_pc => {
+                    high_pc_value = Some(attr.value());
+                }
+                _ => continue,
+            }
+        }
+
+        let (name, low_pc) = match (name, low_pc) {
+            (Some(name), Some(low_pc)) => (name, low_pc),
+            _ => return Ok(None),
+        };
+
+        let high_pc = match high_pc_value {
+            Some(gimli::AttributeValue::Addr(addr)) => addr,
+            Some(value) => value.udata_value().map(|offset| low_pc + offset).unwrap_or(low_pc),
+            None => low_pc,
+        };
+
+        Ok(Some((name, low_pc, high_pc)))
+    }
+
     fn attr_value<R: Reader>(&self, attr: &gimli::Attribute<R>, dwarf: &gimli::Dwarf<R>) -> OsString {
         let value = attr.value();
         match value {
@@ -221,7 +479,145 @@ impl Dwarf {
                     OsString::default()
                 }
             }
-            _ =>  OsString::default()
+            gimli::AttributeValue::Addr(addr) => OsString::from(format!("{:#x}", addr)),
+            _ => match value.udata_value() {
+                Some(data) => OsString::from(data.to_string()),
+                None => OsString::default(),
+            },
+        }
+    }
+}
+
+impl Dwarf {
+    /// Resolves the companion `.dwo` for a skeleton compile unit built with
+    /// `-gsplit-dwarf`, falling back to a packaged `.dwp` (indexed by `dwo_id`) found
+    /// alongside `comp_dir` when no loose `.dwo` exists. Best-effort: an error reading a
+    /// split-DWARF file is returned, and the caller logs it and otherwise ignores it; when no
+    /// companion file is found, `element` silently keeps whatever the skeleton unit provided.
+    fn merge_dwo(&self, comp_dir: &Path, dwo_name: &OsStr, dwo_id: Option<u64>, element: &mut DwarfCompileUnit) -> Result<()> {
+        let dwo_path = comp_dir.join(dwo_name);
+        if dwo_path.is_file() {
+            return self.load_dwo_unit_into(&dwo_path, element);
+        }
+
+        if let Some(dwo_id) = dwo_id {
+            if let Some(dwp_path) = self.find_dwp(comp_dir) {
+                return self.load_dwp_unit_into(&dwp_path, dwo_id, element);
+            }
         }
+
+        Ok(())
+    }
+
+    /// A build commonly packages every unit's split DWARF into a single `.dwp` sitting next
+    /// to the compile unit's sources; look for one rather than requiring its name up front.
+    fn find_dwp(&self, comp_dir: &Path) -> Option<PathBuf> {
+        std::fs::read_dir(comp_dir).ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| path.extension().and_then(|ext| ext.to_str()) == Some("dwp"))
+    }
+
+    fn load_dwo_unit_into(&self, path: &Path, element: &mut DwarfCompileUnit) -> Result<()> {
+        let bytes = std::fs::read(path)?;
+        let object = object::File::parse(&*bytes)?;
+        let endian = if object.is_little_endian() {
+            gimli::RunTimeEndian::Little
+        } else {
+            gimli::RunTimeEndian::Big
+        };
+
+        let arena_data = Arena::new();
+        let mut load_section = |id: gimli::SectionId| -> Result<_> {
+            self.load_dwo_section(id, &object, endian, &arena_data)
+        };
+        let dwo_dwarf = gimli::Dwarf::load(&mut load_section)?;
+
+        self.merge_unit_into(&dwo_dwarf, element)
+    }
+
+    /// Drives gimli's DWARF-package mechanism: loads the `.dwp`'s sections, then resolves
+    /// the single compile unit matching `dwo_id` out of the package rather than assuming the
+    /// package holds only one unit.
+    fn load_dwp_unit_into(&self, path: &Path, dwo_id: u64, element: &mut DwarfCompileUnit) -> Result<()> {
+        let bytes = std::fs::read(path)?;
+        let object = object::File::parse(&*bytes)?;
+        let endian = if object.is_little_endian() {
+            gimli::RunTimeEndian::Little
+        } else {
+            gimli::RunTimeEndian::Big
+        };
+
+        let arena_data = Arena::new();
+        let empty = gimli::EndianSlice::new(&[], endian);
+        let mut load_section = |id: gimli::SectionId| -> Result<_> {
+            self.load_dwo_section(id, &object, endian, &arena_data)
+        };
+        let dwp = gimli::DwarfPackage::load(&mut load_section, empty)?;
+
+        let dwo_dwarf = match dwp.find_cu(gimli::DwoId(dwo_id), &gimli::Dwarf::default())? {
+            Some(dwarf) => dwarf,
+            None => return Ok(()),
+        };
+
+        self.merge_unit_into(&dwo_dwarf, element)
+    }
+
+    /// Merges a DWO-side `Dwarf`'s compile unit attributes and functions into `element`,
+    /// without clobbering anything the skeleton unit already had.
+    fn merge_unit_into<R: Reader>(&self, dwarf: &gimli::Dwarf<R>, element: &mut DwarfCompileUnit) -> Result<()> {
+        let mut iter = dwarf.units();
+        while let Some(header) = iter.next()? {
+            let unit = dwarf.unit(header)?;
+            let mut entries = unit.entries();
+            while let Some((_, entry)) = entries.next_dfs()? {
+                match entry.tag() {
+                    constants::DW_TAG_compile_unit => {
+                        let mut attrs = entry.attrs();
+                        while let Some(attr) = attrs.next()? {
+                            match attr.name() {
+                                constants::DW_AT_comp_dir if element.DW_AT_comp_dir.as_os_str().is_empty() => {
+                                    element.DW_AT_comp_dir.push(&self.attr_value(&attr, dwarf));
+                                }
+                                constants::DW_AT_name if element.DW_AT_name.as_os_str().is_empty() => {
+                                    element.DW_AT_name.push(&self.attr_value(&attr, dwarf));
+                                }
+                                constants::DW_AT_producer if element.DW_AT_producer.is_empty() => {
+                                    element.DW_AT_producer.push_str(&self.attr_value(&attr, dwarf).to_string_lossy());
+                                }
+                                _ => continue,
+                            }
+                        }
+                    }
+                    constants::DW_TAG_subprogram => {
+                        if let Some(function) = self.subprogram_function(entry, dwarf)? {
+                            element.functions.push(function);
+                        }
+                    }
+                    _ => continue,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads a DWO-side section by its `.dwo`-suffixed name (e.g. `.debug_info.dwo`). DWO
+    /// sections never carry relocations, so unlike [`Self::load_file_section`] this returns a
+    /// plain reader with no `Relocate` wrapper.
+    fn load_dwo_section<'input, 'arena, Endian: gimli::Endianity>(
+        &self,
+        id: gimli::SectionId,
+        file: &object::File<'input>,
+        endian: Endian,
+        arena_data: &'arena Arena<Cow<'input, [u8]>>,
+    ) -> Result<gimli::EndianSlice<'arena, Endian>> {
+        let name = id.dwo_name().unwrap_or_else(|| id.name());
+        let data = match file.section_by_name(name) {
+            Some(section) => section.uncompressed_data()?,
+            None => Cow::Owned(Vec::with_capacity(1)),
+        };
+        let data_ref = (*arena_data.alloc(data)).borrow();
+        Ok(gimli::EndianSlice::new(data_ref, endian))
     }
 }
\ No newline at end of file
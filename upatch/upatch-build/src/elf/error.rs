@@ -0,0 +1,61 @@
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+
+/// Structured error for ELF/symbol parsing, so a truncated or malformed section surfaces
+/// a contextual error instead of the bare `.unwrap()` panics the readers used to rely on.
+#[derive(Debug)]
+pub struct ElfError {
+    path: Option<PathBuf>,
+    message: String,
+    source: Option<std::io::Error>,
+}
+
+impl ElfError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            path: None,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(message: impl Into<String>, source: std::io::Error) -> Self {
+        Self {
+            path: None,
+            message: message.into(),
+            source: Some(source),
+        }
+    }
+
+    pub fn at(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+impl Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{} (\"{}\")", self.message, path.display())?,
+            None => write!(f, "{}", self.message)?,
+        }
+        if let Some(source) = &self.source {
+            write!(f, ", {}", source.to_string().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ElfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_ref().map(|e| e as _)
+    }
+}
+
+impl From<std::io::Error> for ElfError {
+    fn from(source: std::io::Error) -> Self {
+        ElfError::with_source("ELF I/O error", source)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ElfError>;
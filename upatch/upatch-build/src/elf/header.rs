@@ -4,42 +4,91 @@ use super::{OperateRead, OperateWrite};
 
 pub const ET_DYN: u16 = 3;
 
+pub const ELFCLASS32: u8 = 1;
+pub const ELFCLASS64: u8 = 2;
+
 pub const EI_OSABI: usize = 7;
 pub const ELFOSABI_GNU: u8 = 0x3;
 pub const ELFOSABI_FREEBSD: u8 = 0x9;
 
 pub trait HeaderRead: OperateRead {
+    /// `ELFCLASS32` or `ELFCLASS64`; the getters below whose field offset differs between the
+    /// 32- and 64-bit layouts dispatch on it (`e_ident` sits at offset 0 in both).
+    fn get_class(&self) -> u8;
+
     fn get_e_ident(&self) -> u128 {
         self.get(offset_of!(FileHeader64, e_ident))
     }
 
     fn get_e_type(&self) -> u16 {
-        self.get(offset_of!(FileHeader64, e_type))
+        match self.get_class() {
+            ELFCLASS32 => self.get(offset_of!(FileHeader32, e_type)),
+            _ => self.get(offset_of!(FileHeader64, e_type)),
+        }
     }
 
     fn get_e_shnum(&self) -> u16 {
-        self.get(offset_of!(FileHeader64, e_shnum))
+        match self.get_class() {
+            ELFCLASS32 => self.get(offset_of!(FileHeader32, e_shnum)),
+            _ => self.get(offset_of!(FileHeader64, e_shnum)),
+        }
     }
 
     fn get_e_shoff(&self) -> u64 {
-        self.get(offset_of!(FileHeader64, e_shoff))
+        match self.get_class() {
+            ELFCLASS32 => self.get::<u32>(offset_of!(FileHeader32, e_shoff)) as u64,
+            _ => self.get(offset_of!(FileHeader64, e_shoff)),
+        }
     }
 
     fn get_e_shentsize(&self) -> u16 {
-        self.get(offset_of!(FileHeader64, e_shentsize))
+        match self.get_class() {
+            ELFCLASS32 => self.get(offset_of!(FileHeader32, e_shentsize)),
+            _ => self.get(offset_of!(FileHeader64, e_shentsize)),
+        }
+    }
+
+    fn get_e_shstrndx(&self) -> u16 {
+        match self.get_class() {
+            ELFCLASS32 => self.get(offset_of!(FileHeader32, e_shstrndx)),
+            _ => self.get(offset_of!(FileHeader64, e_shstrndx)),
+        }
     }
 }
 
-pub trait HeaderWrite: OperateWrite {
+pub trait HeaderWrite: HeaderRead + OperateWrite {
     fn set_e_ident(&mut self, e_ident: u128) {
+        // e_ident sits at offset 0 in both FileHeader32 and FileHeader64, so no class dispatch
+        // is needed here.
         self.set(offset_of!(FileHeader64, e_ident), e_ident)
     }
 
     fn set_e_shnum(&mut self, e_shnum: u16) {
-        self.set(offset_of!(FileHeader64, e_shnum), e_shnum)
+        match self.get_class() {
+            ELFCLASS32 => self.set(offset_of!(FileHeader32, e_shnum), e_shnum),
+            _ => self.set(offset_of!(FileHeader64, e_shnum), e_shnum),
+        }
     }
 }
 
+#[repr(C)]
+pub struct FileHeader32 {
+    pub e_ident: u128,
+    pub e_type: u16,
+    pub e_machine: u16,
+    pub e_version: u32,
+    pub e_entry: u32,
+    pub e_phoff: u32,
+    pub e_shoff: u32,
+    pub e_flags: u32,
+    pub e_ehsize: u16,
+    pub e_phentsize: u16,
+    pub e_phnum: u16,
+    pub e_shentsize: u16,
+    pub e_shnum: u16,
+    pub e_shstrndx: u16,
+}
+
 #[repr(C)]
 pub struct FileHeader64 {
     pub e_ident: u128,
@@ -1,57 +1,94 @@
 use std::fs::{OpenOptions, File};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use memmap2::{MmapOptions, Mmap};
+use anyhow::{anyhow, bail, Context, Result};
+use memmap2::{MmapOptions, MmapMut, Mmap};
 
 use super::super::*;
 use super::header::*;
 use super::section::*;
 use super::symbol::*;
 
+/// `SHT_DYNSYM`, the dynamic-linker's pared-down symbol table. Not re-exported from the
+/// (write-side) section module, which only needs `SHT_SYMTAB` today.
+const SHT_DYNSYM: u32 = 11;
+
+/// `sizeof(Elf32_Sym)`/`sizeof(Elf64_Sym)`: symbol table entries are a fixed, class-dependent
+/// size (16 bytes for 32-bit, 24 for 64-bit), so `symbols()` must pick the right one instead of
+/// always assuming 64-bit layout.
+const SYMBOL_ENTRY_SIZE_32: usize = 16;
+const SYMBOL_ENTRY_SIZE_64: usize = 24;
+
 #[derive(Debug)]
 pub struct Elf {
+    path: PathBuf,
     file: File,
-    _class: u8,
+    read_only: bool,
+    class: u8,
     endian: Endian,
     strtab: Option<Mmap>,
 }
 
 impl Elf {
-    pub fn parse<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
-        let file = OpenOptions::new().read(true).write(true).open(path)?;
-        let mmap = unsafe { MmapOptions::new().offset(0).len(6).map(&file)? };
-        //Noe we only support 64 bit
-        let _class = match mmap.get(4..5) {
+    pub fn parse<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        // Debug-info and vmlinux files are frequently only readable, not writable. Fall back to
+        // a read-only open rather than failing outright; `map()` below then uses a copy-on-write
+        // mapping so the rest of this type doesn't need a second, read-only code path.
+        let (file, read_only) = match OpenOptions::new().read(true).write(true).open(&path) {
+            Ok(file) => (file, false),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                let file = OpenOptions::new().read(true).open(&path)
+                    .with_context(|| format!("Failed to open \"{}\"", path.display()))?;
+                (file, true)
+            },
+            Err(e) => return Err(e).with_context(|| format!("Failed to open \"{}\"", path.display())),
+        };
+
+        let mmap = unsafe { MmapOptions::new().offset(0).len(6).map(&file) }
+            .with_context(|| format!("Failed to map identification bytes of \"{}\"", path.display()))?;
+        let class = match mmap.get(4..5) {
+            Some(&[ELFCLASS32]) => ELFCLASS32,
             Some(&[ELFCLASS64]) => ELFCLASS64,
-            _ => return Err(std::io::Error::new(
-                std::io::ErrorKind::AddrNotAvailable,
-                format!("elf format is not class64")
-            )),
+            _ => bail!("\"{}\" has an unsupported ELF class byte", path.display()),
         };
 
         let endian = match mmap.get(5..6) {
             Some([1]) => Endian::new(Endianness::Little),
             Some([2]) => Endian::new(Endianness::Big),
-            _ => return Err(std::io::Error::new(
-                std::io::ErrorKind::AddrNotAvailable,
-                format!("elf endian is error")
-            )),
+            _ => bail!("\"{}\" has an invalid ELF endianness byte", path.display()),
         };
 
         Ok(Self {
+            path,
             file,
-            _class,
+            read_only,
+            class,
             endian,
             strtab: None
         })
     }
 
-    pub fn header(&mut self) -> std::io::Result<Header> {
-        let mmap = unsafe { MmapOptions::new().offset(0).len(64).map_mut(&self.file)? };
-        Ok(Header::from(mmap, self.endian))
+    /// Maps `len` bytes at `offset`, writable in memory regardless of whether the backing file
+    /// was opened read-only (copy-on-write in that case, so edits never reach disk).
+    fn map(&self, offset: u64, len: usize, what: &str) -> Result<MmapMut> {
+        let mut opts = MmapOptions::new();
+        opts.offset(offset).len(len);
+        let mmap = if self.read_only {
+            unsafe { opts.map_copy(&self.file) }
+        } else {
+            unsafe { opts.map_mut(&self.file) }
+        };
+        mmap.with_context(|| format!("Failed to map {} of \"{}\"", what, self.path.display()))
     }
 
-    pub fn sections(&mut self) -> std::io::Result<Vec<SectionHeader>> {
+    pub fn header(&mut self) -> Result<Header> {
+        let mmap = self.map(0, 64, "file header")?;
+        Ok(Header::from(mmap, self.endian, self.class))
+    }
+
+    pub fn sections(&mut self) -> Result<Vec<SectionHeader>> {
         let mut res = Vec::new();
         let header = self.header()?;
         let offset = header.get_e_shoff() as usize;
@@ -60,38 +97,76 @@ impl Elf {
 
         for i in 0..num {
             let start = (offset + (i * shentsize)) as u64;
-            let mmap = unsafe { MmapOptions::new().offset(start).len(shentsize).map_mut(&self.file)? };
-            res.push(SectionHeader::from(mmap, self.endian));
+            let mmap = self.map(start, shentsize, &format!("section header {}", i))?;
+            res.push(SectionHeader::from(mmap, self.endian, self.class));
         }
 
         Ok(res)
     }
 
-    pub fn symbols(&mut self) -> std::io::Result<Vec<SymbolHeader>> {
+    /// Finds a section by its name (resolved via the section header string table pointed to by
+    /// `e_shstrndx`), or `Ok(None)` if no section carries that name.
+    pub fn section_by_name(&mut self, name: &str) -> Result<Option<SectionHeader>> {
+        let shstrndx = self.header()?.get_e_shstrndx() as usize;
+        let sections = self.sections()?;
+        let shstrtab = sections.get(shstrndx)
+            .ok_or_else(|| anyhow!("\"{}\" has no section header string table (index {})", self.path.display(), shstrndx))?;
+
+        let strtab_offset = shstrtab.get_sh_offset();
+        let strtab_size = shstrtab.get_sh_size() as usize;
+        let strtab = unsafe { MmapOptions::new().offset(strtab_offset).len(strtab_size).map(&self.file) }
+            .with_context(|| format!("Failed to map section header string table of \"{}\"", self.path.display()))?;
+
+        for section in sections {
+            let name_offset = section.get_sh_name() as usize;
+            let section_name = strtab.get(name_offset..)
+                .and_then(|bytes| bytes.split(|&b| b == 0).next())
+                .map(String::from_utf8_lossy)
+                .unwrap_or_default();
+            if section_name == name {
+                return Ok(Some(section));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn symbols(&mut self) -> Result<Vec<SymbolHeader>> {
         let mut res = Vec::new();
         let sections = &self.sections()?;
-        for section in sections {
-            if section.get_sh_type().eq(&SHT_SYMTAB) {
-                let offset =  section.get_sh_offset() as usize;
-                let size_sum = section.get_sh_size() as usize;
-                let size = std::mem::size_of::<SymbolHeader64>();
-                let num = size_sum as usize / size;
-                let strtab_offset = sections[section.get_sh_link() as usize].get_sh_offset();
-                let strtab_size = sections[section.get_sh_link() as usize].get_sh_size() as usize;
-
-                self.strtab = Some(unsafe { MmapOptions::new().offset(strtab_offset).len(strtab_size).map(&self.file)? });
-
-                for i in 0..num {
-                    let start = (offset + (i * size)) as u64;
-                    let mmap = unsafe { MmapOptions::new().offset(start).len(size).map_mut(&self.file)? };
-                    res.push(SymbolHeader::from(mmap, self.endian, &self.strtab.as_ref().unwrap()));
-                }
-                return Ok(res);
+        for (idx, section) in sections.iter().enumerate() {
+            if section.get_sh_type() != SHT_SYMTAB && section.get_sh_type() != SHT_DYNSYM {
+                continue;
+            }
+
+            let offset =  section.get_sh_offset() as usize;
+            let size_sum = section.get_sh_size() as usize;
+            let size = match self.class {
+                ELFCLASS32 => SYMBOL_ENTRY_SIZE_32,
+                _ => SYMBOL_ENTRY_SIZE_64,
+            };
+            let num = size_sum as usize / size;
+
+            let strtab_idx = section.get_sh_link() as usize;
+            let strtab_section = sections.get(strtab_idx).ok_or_else(|| anyhow!(
+                "symbol table section {} of \"{}\" references out-of-range string table index {}",
+                idx, self.path.display(), strtab_idx
+            ))?;
+            let strtab_offset = strtab_section.get_sh_offset();
+            let strtab_size = strtab_section.get_sh_size() as usize;
+
+            self.strtab = Some(
+                unsafe { MmapOptions::new().offset(strtab_offset).len(strtab_size).map(&self.file) }
+                    .with_context(|| format!("Failed to map string table of \"{}\"", self.path.display()))?
+            );
+
+            for i in 0..num {
+                let start = (offset + (i * size)) as u64;
+                let mmap = self.map(start, size, &format!("symbol table entry {}", i))?;
+                res.push(SymbolHeader::from(mmap, self.endian, self.class, self.strtab.as_ref().unwrap()));
             }
+            return Ok(res);
         }
-        Err(std::io::Error::new(
-            std::io::ErrorKind::AddrNotAvailable,
-            format!("elf symbols is error")
-        ))
+        bail!("\"{}\" has no symbol or dynamic symbol table section", self.path.display())
     }
 }
\ No newline at end of file
@@ -20,15 +20,20 @@ use super::super::{Endian, HeaderRead, HeaderWrite, OperateRead, OperateWrite, R
 pub struct Header {
     mmap: MmapMut,
     endian: Endian,
+    class: u8,
 }
 
 impl Header {
-    pub fn from(mmap: MmapMut, endian: Endian) -> Self {
-        Self { mmap, endian }
+    pub fn from(mmap: MmapMut, endian: Endian, class: u8) -> Self {
+        Self { mmap, endian, class }
     }
 }
 
-impl HeaderRead for Header {}
+impl HeaderRead for Header {
+    fn get_class(&self) -> u8 {
+        self.class
+    }
+}
 
 impl HeaderWrite for Header {}
 
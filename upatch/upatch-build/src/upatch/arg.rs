@@ -3,10 +3,19 @@ use std::env;
 use std::path::Path;
 use std::process::exit;
 
+use anyhow::{bail, Context};
+
 use crate::tool::*;
 
 use super::Result;
 use super::Error;
+use super::CrossToolchain;
+
+/// Real implementation of each handler / [`Arg::read`]: uses `anyhow::Result` so every failure
+/// records which flag and which path it was acting on, per the crate-wide push toward contextual
+/// error chaining. The public API still returns the crate's own [`Result`]/[`Error`] (see
+/// [`Arg::read`]), so callers elsewhere in `upatch-build` are unaffected.
+type AnyResult<T> = anyhow::Result<T>;
 
 pub struct Arg {
     pub work_dir: String,
@@ -22,40 +31,200 @@ pub struct Arg {
     program: String,
     pub skip_compiler_check: bool,
     pub verbose: bool,
+    pub jobs: usize,
+    pub target: String,
+    pub qemu_runner: String,
+    pub sandbox: bool,
+}
+
+/// One entry in [`OPTIONS`]: a flag's name(s), whether it takes a value, the `usage()` blurb,
+/// and the handler that applies it to an `Arg`. `value_name` being `Some` is what tells
+/// [`Arg::read`] to look for `--name value` / `--name=value` / `-n value`; `None` means it's a
+/// bare switch.
+struct OptionSpec {
+    short: Option<&'static str>,
+    long: &'static str,
+    value_name: Option<&'static str>,
+    help: &'static str,
+    handler: fn(&mut Arg, Option<&str>) -> AnyResult<()>,
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    OptionSpec { short: Some("-h"), long: "--help", value_name: None, help: "options message", handler: handle_help },
+    OptionSpec { short: Some("-w"), long: "--work-dir", value_name: Some("WORK_DIR"), help: "Specify work directory, default ~/.upatch/, will delete the work_dir when building upatch, use a empty directory", handler: handle_work_dir },
+    OptionSpec { short: Some("-s"), long: "--debug-source", value_name: Some("DEBUG_SOURCE"), help: "Specify source directory, will modify the debug_source when building upatch, use a copy", handler: handle_source },
+    OptionSpec { short: Some("-b"), long: "--build-source-cmd", value_name: Some("BUILD_SOURCE_CMD"), help: "Specify build source command", handler: handle_build_source_cmd },
+    OptionSpec { short: Some("-bp"), long: "--build-patch-cmd", value_name: Some("BUILD_PATCH_CMD"), help: "Specify build patched command, default --build-source-cmd", handler: handle_build_patch_cmd },
+    OptionSpec { short: Some("-i"), long: "--debug-info", value_name: Some("DEBUG_INFO"), help: "Specify debug info", handler: handle_debug_info },
+    OptionSpec { short: Some("-c"), long: "--compiler", value_name: Some("COMPILER"), help: "Specify compiler, default gcc", handler: handle_compiler },
+    OptionSpec { short: Some("-e"), long: "--elf-name", value_name: Some("ELF_NAME"), help: "Specify running file name", handler: handle_elf_name },
+    OptionSpec { short: Some("-o"), long: "--output-dir", value_name: Some("OUTPUT_DIR"), help: "Specify output directory, default --work-dir", handler: handle_output },
+    OptionSpec { short: Some("-n"), long: "--name", value_name: Some("NAME"), help: "Specify output name, default --elf-name", handler: handle_name },
+    OptionSpec { short: None, long: "--skip-compiler-check", value_name: None, help: "Specify skip check compiler", handler: handle_skip_compiler_check },
+    OptionSpec { short: Some("-v"), long: "--verbose", value_name: None, help: "Specify show debug information", handler: handle_verbose },
+    OptionSpec { short: Some("-V"), long: "--version", value_name: None, help: "Specify show version information", handler: handle_version },
+    OptionSpec { short: Some("-j"), long: "--jobs", value_name: Some("JOBS"), help: "Specify worker threads for DWARF scanning, default: number of cores", handler: handle_jobs },
+    OptionSpec { short: None, long: "--target", value_name: Some("TARGET"), help: "Specify target triple for cross-compilation, default: build natively", handler: handle_target },
+    OptionSpec { short: None, long: "--qemu-runner", value_name: Some("QEMU_RUNNER"), help: "Specify qemu-<arch> user-mode runner for cross-compilation probing", handler: handle_qemu_runner },
+    OptionSpec { short: None, long: "--sandbox", value_name: None, help: "Run the build command inside an isolated mount/PID/network namespace", handler: handle_sandbox },
+];
+
+fn handle_work_dir(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    let value = value.unwrap_or_default();
+    if !Path::new(value).is_dir() {
+        bail!("--work-dir \"{}\" is not a directory", value);
+    }
+    arg.work_dir.push_str(stringtify(realpath(value)
+        .with_context(|| format!("Failed to resolve --work-dir \"{}\"", value))?).as_str());
+    Ok(())
+}
+
+fn handle_source(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    let value = value.unwrap_or_default();
+    if !Path::new(value).is_dir() {
+        bail!("--debug-source \"{}\" is not a directory", value);
+    }
+    arg.source.push_str(stringtify(realpath(value)
+        .with_context(|| format!("Failed to resolve --debug-source \"{}\"", value))?).as_str());
+    Ok(())
+}
+
+fn handle_build_source_cmd(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    arg.build_source_command.push_str(value.unwrap_or_default());
+    Ok(())
+}
+
+fn handle_build_patch_cmd(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    arg.build_patch_command.push_str(value.unwrap_or_default());
+    Ok(())
+}
+
+fn handle_debug_info(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    let value = value.unwrap_or_default();
+    if !Path::new(value).is_file() {
+        bail!("--debug-info \"{}\" is not a file", value);
+    }
+    arg.debug_info.push_str(stringtify(realpath(value)
+        .with_context(|| format!("Failed to resolve --debug-info \"{}\"", value))?).as_str());
+    Ok(())
+}
+
+fn handle_compiler(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    let value = value.unwrap_or_default();
+    if !Path::new(value).is_file() {
+        bail!("--compiler \"{}\" is not a file", value);
+    }
+    arg.compiler_file.push_str(stringtify(realpath(value)
+        .with_context(|| format!("Failed to resolve --compiler \"{}\"", value))?).as_str());
+    Ok(())
+}
+
+fn handle_elf_name(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    arg.elf_name.push_str(value.unwrap_or_default());
+    Ok(())
+}
+
+fn handle_output(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    let value = value.unwrap_or_default();
+    if !Path::new(value).is_dir() {
+        bail!("--output-dir \"{}\" is not a directory", value);
+    }
+    arg.output.push_str(stringtify(realpath(value)
+        .with_context(|| format!("Failed to resolve --output-dir \"{}\"", value))?).as_str());
+    Ok(())
+}
+
+fn handle_name(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    arg.patch_name.push_str(value.unwrap_or_default());
+    Ok(())
+}
+
+fn handle_skip_compiler_check(arg: &mut Arg, _value: Option<&str>) -> AnyResult<()> {
+    arg.skip_compiler_check = true;
+    Ok(())
+}
+
+fn handle_verbose(arg: &mut Arg, _value: Option<&str>) -> AnyResult<()> {
+    arg.verbose = true;
+    Ok(())
+}
+
+fn handle_jobs(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    let value = value.unwrap_or_default();
+    arg.jobs = value.parse()
+        .with_context(|| format!("--jobs \"{}\" is not a number", value))?;
+    Ok(())
+}
+
+fn handle_target(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    let value = value.unwrap_or_default();
+    if CrossToolchain::for_target(value).is_none() {
+        bail!("--target \"{}\" is not a supported cross-compilation triple", value);
+    }
+    arg.target.push_str(value);
+    Ok(())
+}
+
+fn handle_qemu_runner(arg: &mut Arg, value: Option<&str>) -> AnyResult<()> {
+    arg.qemu_runner.push_str(value.unwrap_or_default());
+    Ok(())
+}
+
+fn handle_sandbox(arg: &mut Arg, _value: Option<&str>) -> AnyResult<()> {
+    arg.sandbox = true;
+    Ok(())
+}
+
+fn handle_version(_arg: &mut Arg, _value: Option<&str>) -> AnyResult<()> {
+    println!("{}", env!("CARGO_PKG_VERSION"));
+    exit(0);
+}
+
+fn handle_help(arg: &mut Arg, _value: Option<&str>) -> AnyResult<()> {
+    arg.usage();
+    exit(0);
 }
 
 impl Arg {
     fn usage(&self) {
         println!("Usage: {} [options] --debug-source <DEBUG_SOURCE> --build-source-cmd <BUILD_SOURCE_CMD> --debug-info <DEBUG_INFO> --elf-name <ELF_NAME> <PATCHES>", self.program);
-        println!("      -h|--help:                  options message");
-        println!("      -w|--work-dir:              Specify work directory, default ~/.upatch/");
-        println!("                                  will delete the work_dir when building upatch, use a empty directory");
-        println!("      -s|--debug-source:          Specify source directory");
-        println!("                                  will modify the debug_source when building upatch, use a copy");
-        println!("      -b|--build-source-cmd:      Specify build source command");
-        println!("      -bp|--build-patch-cmd:      Specify build patched command, default --build-source-cmd");
-        println!("      -i|--debug-info:            Specify debug info");
-        println!("      -c|--compiler:              Specify compiler, default gcc");
-        println!("      -e|--elf-name:              Specify running file name");
-        println!("      -o|--output-dir:            Specify output directory, default --work-dir");
-        println!("      -n|--name:                  Specify output name, default --elf-name");
-        println!("      --skip-compiler-check:      Specify skip check compiler");
-        println!("      -v|--verbose:               Specify show debug information");
-        println!("      -V|--version:               Specify show version information");
+        for spec in OPTIONS {
+            let names = match spec.short {
+                Some(short) => format!("{}|{}", short, spec.long),
+                None => spec.long.to_string(),
+            };
+            let padding = " ".repeat(28usize.saturating_sub(names.len()));
+            println!("      {}:{}{}", names, padding, spec.help);
+        }
     }
 
-    fn check(&mut self) -> Result<()>  {
-        if self.source.is_empty() ||
-            self.debug_info.is_empty() ||
-            self.diff_file.is_empty() ||
-            self.elf_name.is_empty() ||
-            self.build_source_command.is_empty() {
+    fn check(&mut self) -> AnyResult<()>  {
+        let mut missing = Vec::new();
+        if self.source.is_empty() {
+            missing.push("--debug-source");
+        }
+        if self.debug_info.is_empty() {
+            missing.push("--debug-info");
+        }
+        if self.diff_file.is_empty() {
+            missing.push("<PATCHES>");
+        }
+        if self.elf_name.is_empty() {
+            missing.push("--elf-name");
+        }
+        if self.build_source_command.is_empty() {
+            missing.push("--build-source-cmd");
+        }
+        if !missing.is_empty() {
             self.usage();
-            return Err(Error::InvalidInput(format!("no input files")));
+            bail!("missing required argument(s): {}", missing.join(", "));
         }
         if self.build_patch_command.is_empty() {
             self.build_patch_command = self.build_source_command.clone();
         }
+        if !self.qemu_runner.is_empty() && self.target.is_empty() {
+            bail!("--qemu-runner requires --target");
+        }
         Ok(())
     }
 }
@@ -76,102 +245,100 @@ impl Arg {
             program: String::new(),
             skip_compiler_check: false,
             verbose: false,
+            jobs: 0,
+            target: String::new(),
+            qemu_runner: String::new(),
+            sandbox: false,
+        }
+    }
+
+    /// Builds the cross-compilation toolchain requested via `--target`/`--qemu-runner`, if any.
+    pub fn cross_toolchain(&self) -> Option<CrossToolchain> {
+        if self.target.is_empty() {
+            return None;
         }
+        let toolchain = CrossToolchain::for_target(&self.target)?;
+        Some(match self.qemu_runner.is_empty() {
+            true => toolchain,
+            false => toolchain.with_qemu_runner(self.qemu_runner.clone()),
+        })
     }
 
+    /// Walks `env::args()` against [`OPTIONS`], supporting both `--name value` and
+    /// `--name=value`. Unlike the old hand-rolled parser, a flag with a missing value or an
+    /// unknown-but-missing positional patch file fails with a contextual error instead of
+    /// panicking on an out-of-bounds `args[i]`. Internally built on `anyhow::Result` so every
+    /// failure names the offending flag or path (see [`read_impl`](Self::read_impl)); the public
+    /// signature still returns the crate's own [`Result`]/[`Error`] for compatibility with the
+    /// rest of `upatch-build`.
     pub fn read(&mut self) -> Result<()> {
+        self.read_impl().map_err(|e| Error::InvalidInput(format!("{:#}", e)))
+    }
+
+    fn read_impl(&mut self) -> AnyResult<()> {
         let args: Vec<String> = env::args().collect();
         self.program.push_str(&args[0]);
+
         let mut i = 1;
         while i < args.len() {
-            match args[i].as_str() {
-                "-w" | "--work-dir" => {
-                    i += 1;
-                    if !Path::new(&args[i]).is_dir() {
-                        self.usage();
-                        return Err(Error::InvalidInput(format!("workdir {} is not a directory", &args[i])));
-                    }
-                    self.work_dir.push_str(stringtify(realpath(&args[i])?).as_str());
-                },
-                "-s" | "--debug-source" => {
-                    i += 1;
-                    if !Path::new(&args[i]).is_dir() {
-                        self.usage();
-                        return Err(Error::InvalidInput(format!("debugsource {} is not a directory", &args[i])));
-                    }
-                    self.source.push_str(stringtify(realpath(&args[i])?).as_str());
-                },
-                "-b" | "--build-source-cmd" => {
-                    i += 1;
-                    self.build_source_command.push_str(&args[i]);
-                },
-                "-bp" | "--build-patch-cmd" => {
-                    i += 1;
-                    self.build_patch_command.push_str(&args[i]);
-                },
-                "-i" | "--debug-info" => {
-                    i += 1;
-                    if !Path::new(&args[i]).is_file() {
-                        self.usage();
-                        return Err(Error::InvalidInput(format!("debuginfo {} is not a file", &args[i])));
-                    }
-                    self.debug_info.push_str(stringtify(realpath(&args[i])?).as_str());
-                },
-                "-e" | "--elf-name" => {
-                    i += 1;
-                    self.elf_name.push_str(&args[i]);
-                },
-                "-c" | "--compiler" => {
-                    i += 1;
-                    if !Path::new(&args[i]).is_file() {
-                        self.usage();
-                        return Err(Error::InvalidInput(format!("compiler {} is not a file", &args[i])));
-                    }
-                    self.compiler_file.push_str(stringtify(realpath(&args[i])?).as_str());
-                },
-                "-o" | "--output-dir" => {
-                    i += 1;
-                    if !Path::new(&args[i]).is_dir() {
+            // Only treat "=" as the flag/value separator when the left-hand side is actually a
+            // known flag name; a positional patch-file path containing a literal "=" (e.g.
+            // "fix=1.patch") must be kept whole, both for this lookup and for the `is_file`
+            // check below.
+            let (name, inline_value) = match args[i].split_once('=') {
+                Some((name, value)) if OPTIONS.iter().any(|spec| spec.long == name || spec.short == Some(name)) => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                _ => (args[i].clone(), None),
+            };
+
+            let spec = OPTIONS.iter().find(|spec| spec.long == name || spec.short == Some(name.as_str()));
+            let spec = match spec {
+                Some(spec) => spec,
+                None => {
+                    if !Path::new(&name).is_file() {
                         self.usage();
-                        return Err(Error::InvalidInput(format!("output {} is not a file", &args[i])));
+                        bail!("patch \"{}\" is not a file", name);
                     }
-                    self.output.push_str(stringtify(realpath(&args[i])?).as_str());
-                },
-                "-n" | "--name" => {
+                    self.diff_file.push(stringtify(realpath(&name)
+                        .with_context(|| format!("Failed to resolve patch \"{}\"", name))?));
                     i += 1;
-                    self.patch_name.push_str(&args[i]);
-                },
-                "-h" | "--help" => {
-                    self.usage();
-                    exit(0);
-                },
-                "--skip-compiler-check" => {
-                    self.skip_compiler_check = true;
-                },
-                "-v" | "--verbose" => {
-                    self.verbose = true;
-                },
-                "-V" | "--version" => {
-                    println!("{}", env!("CARGO_PKG_VERSION"));
-                    exit(0);
-                },
-                _ => {
-                    if !Path::new(&args[i]).is_file() {
-                        self.usage();
-                        return Err(Error::InvalidInput(format!("patch {} is not a file", args[i])));
+                    continue;
+                }
+            };
+
+            let value = match spec.value_name {
+                None => None,
+                Some(_) => match inline_value {
+                    Some(value) => Some(value),
+                    None => {
+                        i += 1;
+                        match args.get(i) {
+                            Some(value) => Some(value.clone()),
+                            None => {
+                                self.usage();
+                                bail!("{} requires a value", name);
+                            }
+                        }
                     }
-                    self.diff_file.push(stringtify(realpath(&args[i])?));
                 },
+            };
+
+            if let Err(e) = (spec.handler)(self, value.as_deref()) {
+                self.usage();
+                return Err(e).with_context(|| format!("Failed to apply option \"{}\"", name));
             }
+
             i += 1;
         }
+
         self.check()
     }
 }
 
 impl Display for Arg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "work_dir: {}, source: {}, build source command: {}, build patch command: {}, debug info: {}, compiler file: {}, elf_name{}, output: {}, patch_name{}, diff files: {:?}, skip_compiler_check: {}",
+        write!(f, "work_dir: {}, source: {}, build source command: {}, build patch command: {}, debug info: {}, compiler file: {}, elf_name{}, output: {}, patch_name{}, diff files: {:?}, skip_compiler_check: {}, jobs: {}, target: {}, qemu_runner: {}, sandbox: {}",
             self.work_dir,
             self.source,
             self.build_source_command,
@@ -183,6 +350,10 @@ impl Display for Arg {
             self.patch_name,
             self.diff_file,
             self.skip_compiler_check,
+            self.jobs,
+            self.target,
+            self.qemu_runner,
+            self.sandbox,
             )
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,11 @@
 use std::{path::Path, env};
 use std::process::Command;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, OpenOptions, File};
 use std::io::{self, Read};
 
 use walkdir::WalkDir;
+use rayon::prelude::*;
 
 use super::Arg;
 use crate::dwarf::{Dwarf, DwarfCompileUnit};
@@ -78,9 +79,10 @@ impl UpatchBuild {
         compiler.hack()?;
 
         // build source
+        let cross = self.args.cross_toolchain();
         println!("Building original {}", project_name.to_str().unwrap());
         let project = Project::new(self.args.source.clone());
-        project.build(CMD_SOURCE_ENTER, &self.source_dir, self.args.build_source_command.clone())?;
+        project.build(CMD_SOURCE_ENTER, &self.source_dir, self.args.build_source_command.clone(), cross.as_ref(), self.args.sandbox)?;
 
         // build patch
         for patch in &self.args.diff_file {
@@ -89,7 +91,7 @@ impl UpatchBuild {
         }
 
         println!("Building patched {}", project_name.to_str().unwrap());
-        project.build(CMD_PATCHED_ENTER, &self.patch_dir, self.args.build_patch_command.clone())?;
+        project.build(CMD_PATCHED_ENTER, &self.patch_dir, self.args.build_patch_command.clone(), cross.as_ref(), self.args.sandbox)?;
 
         // unhack compiler
         println!("Unhacking compiler");
@@ -101,12 +103,13 @@ impl UpatchBuild {
         let mut patch_obj: HashMap<String, String> = HashMap::new();
         let dwarf = Dwarf::new();
 
-        self.correlate_obj(&dwarf, self.args.source.as_str(), &self.source_dir, &mut source_obj)?;
-        self.correlate_obj(&dwarf, self.args.source.as_str(), &self.patch_dir, &mut patch_obj)?;
+        let source_unmatched = self.correlate_obj(&dwarf, self.args.source.as_str(), &self.source_dir, &mut source_obj)?;
+        let patch_unmatched = self.correlate_obj(&dwarf, self.args.source.as_str(), &self.patch_dir, &mut patch_obj)?;
+        self.correlate_by_signature(&dwarf, &source_unmatched, &patch_unmatched, &mut source_obj, &mut patch_obj)?;
 
         // choose the binary's obj to create upatch file
         let binary_obj = dwarf.file_in_binary(self.args.source.clone(), self.args.elf_name.clone())?;
-        self.correlate_diff(&source_obj, &patch_obj, &binary_obj)?;
+        self.correlate_diff(&dwarf, &source_obj, &patch_obj, &binary_obj)?;
 
         // ld patchs
         let output_file = &format!("{}/{}", &self.args.output, &self.args.patch_name);
@@ -154,23 +157,109 @@ impl UpatchBuild {
         }
     }
 
-    fn correlate_obj(&self, dwarf: &Dwarf, comp_dir: &str, dir: &str, map: &mut HashMap<String, String>) -> Result<()> {
-        let arr = WalkDir::new(dir).into_iter()
+    /// Correlates `.o` files under `dir` to their source path via DWARF `comp_dir`+`name`.
+    /// Returns the paths of objects that couldn't be correlated this way (ambiguous or
+    /// missing compile unit, or a `comp_dir` that doesn't agree with `comp_dir`), so the
+    /// caller can fall back to [`Self::correlate_by_signature`] for them.
+    ///
+    /// Each `.o`'s DWARF is independent and read-only, so the scan itself runs on a capped
+    /// `rayon` thread pool (see [`Self::build_thread_pool`]); only the result is merged into
+    /// `map` afterward, so the shared `&mut HashMap` is never touched during the parallel part.
+    fn correlate_obj(&self, dwarf: &Dwarf, comp_dir: &str, dir: &str, map: &mut HashMap<String, String>) -> Result<Vec<String>> {
+        let objects = WalkDir::new(dir).into_iter()
                 .filter_map(|e| e.ok())
                 .filter(|e| e.path().is_file() && (stringtify(e.path().extension().unwrap_or_default()) == "o"))
+                .map(|e| e.path().to_str().unwrap_or_default().to_string())
                 .collect::<Vec<_>>();
-        for obj in arr {       
-            let name = obj.path().to_str().unwrap_or_default().to_string(); 
-            let result = dwarf.file_in_obj(name.clone())?;
+
+        let pool = self.build_thread_pool()?;
+        let parsed: Vec<(String, io::Result<Vec<DwarfCompileUnit>>)> = pool.install(|| {
+            objects.par_iter()
+                .map(|name| (name.clone(), dwarf.file_in_obj(name.clone())))
+                .collect()
+        });
+
+        let mut unmatched = Vec::new();
+        for (name, result) in parsed {
+            let result = result?;
             if result.len() == 1 && result[0].DW_AT_comp_dir.find(comp_dir) != None{
-                map.insert(result[0].get_source(), name.clone());
+                map.insert(result[0].get_source(), name);
+            } else {
+                unmatched.push(name);
             }
         }
+        Ok(unmatched)
+    }
+
+    /// Builds the thread pool used to parallelize DWARF scanning, capped at `--jobs` worker
+    /// threads so the build doesn't oversubscribe when invoked from an already-parallel
+    /// `make -jN`. `--jobs 0` (the default) leaves the cap to rayon's own heuristic.
+    fn build_thread_pool(&self) -> Result<rayon::ThreadPool> {
+        let mut builder = rayon::ThreadPoolBuilder::new();
+        if self.args.jobs > 0 {
+            builder = builder.num_threads(self.args.jobs);
+        }
+        builder.build().map_err(|e| Error::InvalidInput(format!("failed to create thread pool: {}", e)))
+    }
+
+    /// Pairs objects that `correlate_obj` couldn't match by `comp_dir` (out-of-tree or
+    /// relocated builds) using content signatures instead: each unmatched patch object is
+    /// paired with the unmatched source object with which it shares the most matching
+    /// function symbol signatures, then inserted into `source_obj`/`patch_obj` under a shared
+    /// key so `correlate_diff` can still find the pair.
+    fn correlate_by_signature(&self, dwarf: &Dwarf, source_unmatched: &[String], patch_unmatched: &[String], source_obj: &mut HashMap<String, String>, patch_obj: &mut HashMap<String, String>) -> Result<()> {
+        if source_unmatched.is_empty() || patch_unmatched.is_empty() {
+            return Ok(());
+        }
+
+        let mut source_signatures: HashMap<u64, String> = HashMap::new();
+        for source in source_unmatched {
+            if let Ok(signatures) = dwarf.symbol_signatures(source) {
+                for hash in signatures.values() {
+                    source_signatures.insert(*hash, source.clone());
+                }
+            }
+        }
+
+        for patch in patch_unmatched {
+            let signatures = match dwarf.symbol_signatures(patch) {
+                Ok(signatures) => signatures,
+                Err(_) => continue,
+            };
+
+            let mut votes: HashMap<&str, usize> = HashMap::new();
+            for hash in signatures.values() {
+                if let Some(source) = source_signatures.get(hash) {
+                    *votes.entry(source.as_str()).or_insert(0) += 1;
+                }
+            }
+
+            let source = match votes.into_iter().max_by_key(|(_, count)| *count) {
+                Some((source, _)) => source,
+                None => continue,
+            };
+
+            // `correlate_diff` looks pairs up by the *source path* DWARF attributes the
+            // compiled binary carries (the same key `correlate_obj` inserts under), not by
+            // either side's `.o` object path -- re-derive it here instead of keying by
+            // `source`/`patch`, which are just object paths and can never match that lookup.
+            let source_units = match dwarf.file_in_obj(source) {
+                Ok(units) => units,
+                Err(_) => continue,
+            };
+            let key = match source_units.first() {
+                Some(unit) => unit.get_source(),
+                None => continue,
+            };
+
+            source_obj.entry(key.clone()).or_insert_with(|| source.to_string());
+            patch_obj.insert(key, patch.clone());
+        }
+
         Ok(())
     }
 
-    fn correlate_diff(&self, source_obj: &HashMap<String, String>, patch_obj: &HashMap<String, String>, binary_obj: &Vec<DwarfCompileUnit>) -> Result<()> {
-        // TODO can print changed function
+    fn correlate_diff(&self, dwarf: &Dwarf, source_obj: &HashMap<String, String>, patch_obj: &HashMap<String, String>, binary_obj: &Vec<DwarfCompileUnit>) -> Result<()> {
         for path in binary_obj {
             let source_name = path.get_source();
             match &patch_obj.get(&source_name) {
@@ -178,6 +267,8 @@ impl UpatchBuild {
                     let output_dir =  self.output_dir.clone() + "/" + Path::new(patch).file_name().unwrap().to_str().unwrap();
                     match &source_obj.get(&source_name) {
                         Some(source) => {
+                            self.print_changed_functions(dwarf, source, patch);
+
                             let output = Command::new(&self.tool_file)
                                         .args(["-s", &source, "-p", &patch, "-o", &output_dir, "-r", &self.args.debug_info])
                                         .stdout(OpenOptions::new().append(true).write(true).open(&self.log_file)?)
@@ -196,6 +287,35 @@ impl UpatchBuild {
         Ok(())
     }
 
+    /// Prints the names of functions present in only one of the matched source and patch
+    /// objects (added or removed), by diffing the `DW_TAG_subprogram` name sets each side
+    /// carries; a function named on both sides is not listed, even if its body changed. Best-effort:
+    /// if either object can't be re-parsed, the build continues without this diagnostic.
+    fn print_changed_functions(&self, dwarf: &Dwarf, source: &str, patch: &str) {
+        let source_functions = match dwarf.file_in_obj(source) {
+            Ok(units) => units,
+            Err(_) => return,
+        };
+        let patch_functions = match dwarf.file_in_obj(patch) {
+            Ok(units) => units,
+            Err(_) => return,
+        };
+
+        let source_names: HashSet<String> = source_functions.iter()
+            .flat_map(|unit| unit.functions.iter().map(|(name, _, _)| name.clone()))
+            .collect();
+        let patch_names: HashSet<String> = patch_functions.iter()
+            .flat_map(|unit| unit.functions.iter().map(|(name, _, _)| name.clone()))
+            .collect();
+
+        let mut changed: Vec<&String> = source_names.symmetric_difference(&patch_names).collect();
+        if changed.is_empty() {
+            return;
+        }
+        changed.sort();
+        println!("Changed functions in {}: {}", patch, changed.into_iter().cloned().collect::<Vec<_>>().join(", "));
+    }
+
     fn search_tool(&mut self) -> Result<()> {
         match find_file("./", SUPPORT_TOOL, false, false) {
             Err(_) => {
@@ -0,0 +1,74 @@
+/// Per-target-triple cross-compilation defaults: the `make ARCH=`/`CROSS_COMPILE=` values and
+/// prefixed binutils names needed to build a patch for an architecture other than the host's,
+/// plus an optional `qemu-<arch>` user-mode runner for build-time executable probing.
+#[derive(Debug, Clone)]
+pub struct CrossToolchain {
+    target: String,
+    arch: &'static str,
+    cross_compile_prefix: &'static str,
+    qemu_runner: Option<String>,
+}
+
+/// (target triple, `ARCH=` value, `CROSS_COMPILE=` prefix)
+const CROSS_TOOLCHAINS: &[(&str, &str, &str)] = &[
+    ("aarch64-linux-gnu", "arm64", "aarch64-linux-gnu-"),
+    ("riscv64gc-unknown-linux-gnu", "riscv", "riscv64-linux-gnu-"),
+    ("s390x-linux-gnu", "s390", "s390x-linux-gnu-"),
+];
+
+impl CrossToolchain {
+    /// Looks up built-in defaults for `target`, returning `None` for an unsupported triple so
+    /// `--target` can be rejected instead of silently falling back to a native build.
+    pub fn for_target(target: &str) -> Option<Self> {
+        CROSS_TOOLCHAINS.iter()
+            .find(|(triple, _, _)| *triple == target)
+            .map(|(triple, arch, cross_compile_prefix)| Self {
+                target: triple.to_string(),
+                arch,
+                cross_compile_prefix,
+                qemu_runner: None,
+            })
+    }
+
+    pub fn with_qemu_runner(mut self, runner: impl Into<String>) -> Self {
+        self.qemu_runner = Some(runner.into());
+        self
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn arch(&self) -> &str {
+        self.arch
+    }
+
+    pub fn cross_compile_prefix(&self) -> &str {
+        self.cross_compile_prefix
+    }
+
+    pub fn compiler(&self) -> String {
+        format!("{}gcc", self.cross_compile_prefix)
+    }
+
+    pub fn assembler(&self) -> String {
+        format!("{}as", self.cross_compile_prefix)
+    }
+
+    pub fn linker(&self) -> String {
+        format!("{}ld", self.cross_compile_prefix)
+    }
+
+    pub fn qemu_runner(&self) -> Option<&str> {
+        self.qemu_runner.as_deref()
+    }
+
+    /// `make` variables that redirect a `Kbuild`-style invocation at this toolchain instead of
+    /// the host's native compiler.
+    pub fn make_args(&self) -> [String; 2] {
+        [
+            format!("ARCH={}", self.arch),
+            format!("CROSS_COMPILE={}", self.cross_compile_prefix),
+        ]
+    }
+}
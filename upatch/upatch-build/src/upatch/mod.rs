@@ -2,6 +2,7 @@ mod args;
 mod build;
 mod build_root;
 mod compiler;
+mod cross_toolchain;
 mod error;
 mod link_message;
 mod note;
@@ -13,6 +14,7 @@ pub use args::*;
 pub use build::*;
 pub use build_root::*;
 pub use compiler::*;
+pub use cross_toolchain::*;
 pub use error::*;
 pub use link_message::*;
 pub use note::*;
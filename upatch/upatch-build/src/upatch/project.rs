@@ -1,5 +1,6 @@
 use std::fs::File;
 use std::io::Write;
+use std::os::unix::process::ExitStatusExt;
 use std::path::{Path, PathBuf};
 
 use log::{Level, log};
@@ -9,16 +10,91 @@ use crate::cmd::*;
 use super::Result;
 use super::Error;
 use super::LINK_LOG;
+use super::CrossToolchain;
 
 const COMPILER_CMD_ENV: &str = "UPATCH_CMD";
 const ASSEMBLER_DIR_ENV: &str = "UPATCH_AS_OUTPUT";
 const LINK_PATH_ENV: &str = "UPATCH_LINK_OUTPUT";
+const ASSEMBLER_CMD_ENV: &str = "UPATCH_AS";
+const LINKER_CMD_ENV: &str = "UPATCH_LD";
+const QEMU_RUNNER_ENV: &str = "UPATCH_QEMU_RUNNER";
 const BUILD_SHELL: &str ="build.sh";
+const SANDBOX_SHELL: &str = "sandbox.sh";
 
 pub struct Project {
     project_dir: PathBuf,
 }
 
+/// Describes why a command failed, distinguishing termination by signal (SIGSEGV/SIGKILL/...)
+/// from a normal nonzero exit so callers don't see a misleading "exit code 0" for a build
+/// command that was actually killed.
+fn describe_failure(cmd: &str, exit_code: i32, exit_status: std::process::ExitStatus, stderr: impl std::fmt::Debug) -> String {
+    match exit_status.signal() {
+        Some(signal) => format!("{} terminated by signal {} ({})", cmd, signal, signal_name(signal)),
+        None => format!("{} exited with code {}: {:?}", cmd, exit_code, stderr),
+    }
+}
+
+/// Writes the wrapper script `unshare` runs in place of `sh` directly. A fresh `unshare
+/// --mount` namespace starts as a full, still-writable copy of the host's mount tree, so
+/// binding the project/output directories onto themselves would be a no-op; real isolation
+/// needs the rest of the tree remounted read-only first. The script:
+/// 1. marks `/` (and everything under it) private so none of the following remounts leak
+///    back out to the host's mount namespace;
+/// 2. recursively rebind-mounts `/` onto itself, duplicating every submount (`/home`,
+///    `/boot`, a separate `/var`, an NFS project dir, ...) at its original path in the new
+///    namespace;
+/// 3. remounts each of those submounts read-only *individually* -- a single `remount,bind,ro`
+///    on `/` only affects the root mount itself, since Linux does not propagate a `remount`
+///    over the nested mounts `--rbind` just created (the same per-submount approach
+///    runc/bubblewrap use), so every submount needs its own `remount,bind,ro`;
+/// 4. re-binds the project and assembler-output directories read-write over that read-only
+///    tree, since the build needs to write there;
+/// 5. mounts a private `/proc` for the new PID namespace, then hands off to the build shell.
+/// Running under `--net` already leaves the namespace with no interfaces but loopback, so the
+/// build command has no route to the internet either.
+fn write_sandbox_shell<P: AsRef<Path>>(
+    path: P,
+    project_dir: &Path,
+    assembler_output: &Path,
+    build_shell: &Path,
+) -> std::io::Result<()> {
+    let script = format!(
+        "#!/bin/sh\n\
+         set -e\n\
+         mount --make-rprivate /\n\
+         mount --rbind / /\n\
+         for mnt in $(awk '{{print $5}}' /proc/self/mountinfo | sort -r); do\n\
+         \tmount -o remount,bind,ro -- \"$mnt\" \"$mnt\" 2>/dev/null || true\n\
+         done\n\
+         mount --bind -o rw -- '{project_dir}' '{project_dir}'\n\
+         mount --bind -o rw -- '{assembler_output}' '{assembler_output}'\n\
+         mount -t proc proc /proc\n\
+         exec sh '{build_shell}'\n",
+        project_dir = project_dir.display(),
+        assembler_output = assembler_output.display(),
+        build_shell = build_shell.display(),
+    );
+    std::fs::write(path, script)
+}
+
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        15 => "SIGTERM",
+        _ => "unknown signal",
+    }
+}
+
 impl Project {
     pub fn new<P: AsRef<Path>>(project_dir: P) -> Self {
         Self {
@@ -26,19 +102,46 @@ impl Project {
         }
     }
 
-    pub fn build<P: AsRef<Path>>(&self, cmd: &str, assembler_output: P, build_command: String) -> Result<()> {
+    pub fn build<P: AsRef<Path>>(&self, cmd: &str, assembler_output: P, build_command: String, cross: Option<&CrossToolchain>, sandboxed: bool) -> Result<()> {
         let assembler_output = assembler_output.as_ref();
         let link_output = assembler_output.join(LINK_LOG);
         let command_shell_path = assembler_output.join(BUILD_SHELL);
         let mut command_shell = File::create(&command_shell_path)?;
         command_shell.write_all(build_command.as_ref())?;
-        let args_list = ExternCommandArgs::new().arg(command_shell_path);
-        let envs_list = ExternCommandEnvs::new().env(COMPILER_CMD_ENV, cmd)
+        let mut envs_list = ExternCommandEnvs::new().env(COMPILER_CMD_ENV, cmd)
             .env(ASSEMBLER_DIR_ENV, assembler_output)
             .env(LINK_PATH_ENV, link_output);
-        let output = ExternCommand::new("sh").execve(args_list, envs_list, &self.project_dir)?;
+        if let Some(cross) = cross {
+            // Override the native compiler token set above with the cross one; leaving `cmd`
+            // in place here would silently build for the host arch under a `--target` flag.
+            envs_list = envs_list
+                .env(COMPILER_CMD_ENV, cross.compiler())
+                .env(ASSEMBLER_CMD_ENV, cross.assembler())
+                .env(LINKER_CMD_ENV, cross.linker());
+            if let Some(qemu_runner) = cross.qemu_runner() {
+                envs_list = envs_list.env(QEMU_RUNNER_ENV, qemu_runner);
+            }
+        }
+
+        let output = match sandboxed {
+            false => {
+                let args_list = ExternCommandArgs::new().arg(&command_shell_path);
+                ExternCommand::new("sh").execve(args_list, envs_list, &self.project_dir)?
+            },
+            true => {
+                let sandbox_shell_path = assembler_output.join(SANDBOX_SHELL);
+                write_sandbox_shell(&sandbox_shell_path, &self.project_dir, assembler_output, &command_shell_path)?;
+                let args_list = ExternCommandArgs::new()
+                    .arg("--mount").arg("--pid").arg("--net").arg("--mount-proc").arg("--fork")
+                    .arg("--").arg("sh").arg(&sandbox_shell_path);
+                ExternCommand::new("unshare").execve(args_list, envs_list, &self.project_dir)?
+            },
+        };
         if !output.exit_status().success() {
-            return Err(Error::Project(format!("build project error {}: {:?}", output.exit_code(), output.stderr())))
+            return Err(Error::Project(format!(
+                "build project error: {}",
+                describe_failure(BUILD_SHELL, output.exit_code(), output.exit_status(), output.stderr())
+            )))
         };
         Ok(())
     }
@@ -78,7 +181,10 @@ impl Project {
     fn patch(&self, file: File, args_list: ExternCommandArgs, level: Level) -> Result<()> {
         let output = ExternCommand::new("patch").execvp_stdio_level(args_list, &self.project_dir, file, level)?;
         if !output.exit_status().success() {
-            return Err(Error::Project(format!("error {}: {:?}", output.exit_code(), output.stderr())));
+            return Err(Error::Project(format!(
+                "error: {}",
+                describe_failure("patch", output.exit_code(), output.exit_status(), output.stderr())
+            )));
         };
         Ok(())
     }
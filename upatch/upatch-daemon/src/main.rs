@@ -1,7 +1,7 @@
 use std::{
     fs::{self, Permissions},
     os::unix::fs::PermissionsExt,
-    path::Path,
+    path::{Path, PathBuf},
     process,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -14,8 +14,8 @@ use anyhow::{ensure, Context, Result};
 use daemonize::Daemonize;
 use jsonrpc_core::IoHandler;
 use jsonrpc_ipc_server::{Server, ServerBuilder};
-use log::{error, info, LevelFilter};
-use signal_hook::consts::TERM_SIGNALS;
+use log::{error, info, warn, LevelFilter};
+use signal_hook::consts::{SIGHUP, TERM_SIGNALS};
 
 use syscare_common::os;
 
@@ -45,6 +45,7 @@ const SOCKET_FILE_PERMISSION: u32 = 0o666;
 struct Daemon {
     args: Arguments,
     term_flag: Arc<AtomicBool>,
+    reload_flag: Arc<AtomicBool>,
 }
 
 impl Daemon {
@@ -56,6 +57,7 @@ impl Daemon {
         let instance = Self {
             args: Arguments::new()?,
             term_flag: Arc::new(AtomicBool::new(false)),
+            reload_flag: Arc::new(AtomicBool::new(false)),
         };
 
         ensure!(
@@ -115,13 +117,17 @@ impl Daemon {
         Ok(())
     }
 
-    fn initialize_skeleton(&self) -> Result<IoHandler> {
+    fn config_file(&self) -> PathBuf {
+        self.args.config_dir.join(CONFIG_FILE_NAME)
+    }
+
+    fn initialize_skeleton(&self) -> Result<(IoHandler, SkeletonImpl)> {
         let mut io_handler = IoHandler::new();
 
-        let config_file = self.args.config_dir.join(CONFIG_FILE_NAME);
-        io_handler.extend_with(SkeletonImpl::new(config_file)?.to_delegate());
+        let skeleton = SkeletonImpl::new(self.config_file())?;
+        io_handler.extend_with(skeleton.clone().to_delegate());
 
-        Ok(io_handler)
+        Ok((io_handler, skeleton))
     }
 
     fn initialize_signal_handler(&self) -> Result<()> {
@@ -129,10 +135,22 @@ impl Daemon {
             signal_hook::flag::register(*signal, self.term_flag.clone())
                 .with_context(|| format!("Failed to register handler for signal {}", signal))?;
         }
+        signal_hook::flag::register(SIGHUP, self.reload_flag.clone())
+            .with_context(|| format!("Failed to register handler for signal {}", SIGHUP))?;
 
         Ok(())
     }
 
+    /// Re-reads `upatchd.yaml` and pushes the new settings into the live `SkeletonImpl`,
+    /// leaving the IPC socket and any in-flight RPC handlers untouched.
+    fn reload_config(&self, skeleton: &SkeletonImpl) {
+        info!("Reloading configuration...");
+        match skeleton.reload(self.config_file()) {
+            Ok(_) => info!("Configuration reloaded"),
+            Err(e) => warn!("Failed to reload configuration: {:?}", e),
+        }
+    }
+
     fn start_rpc_server(&self, io_handler: IoHandler) -> Result<Server> {
         let socket_file = self.args.work_dir.join(SOCKET_FILE_NAME);
         let builder = ServerBuilder::new(io_handler).set_client_buffer_size(1);
@@ -166,7 +184,7 @@ impl Daemon {
             .context("Failed to initialize signal handler")?;
 
         info!("Initializing skeleton...");
-        let io_handler = instance
+        let (io_handler, skeleton) = instance
             .initialize_skeleton()
             .context("Failed to initialize skeleton")?;
 
@@ -177,6 +195,9 @@ impl Daemon {
 
         info!("Daemon is running...");
         while !instance.term_flag.load(Ordering::Relaxed) {
+            if instance.reload_flag.swap(false, Ordering::Relaxed) {
+                instance.reload_config(&skeleton);
+            }
             std::thread::park_timeout(Duration::from_millis(DAEMON_PARK_TIME));
         }
 
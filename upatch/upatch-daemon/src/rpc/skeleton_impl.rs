@@ -1,7 +1,11 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::{Context, Result};
 use log::{debug, info};
+use parking_lot::RwLock;
 
 use crate::hijacker::Hijacker;
 
@@ -10,17 +14,30 @@ use super::{
     skeleton::Skeleton,
 };
 
+#[derive(Clone)]
 pub struct SkeletonImpl {
-    hijacker: Hijacker,
+    hijacker: Arc<RwLock<Hijacker>>,
 }
 
 impl SkeletonImpl {
     pub fn new<P: AsRef<Path>>(config_path: P) -> Result<Self> {
         debug!("Initializing hijacker...");
+        let hijacker = Hijacker::new(config_path).context("Failed to initialize hijiacker")?;
+
         Ok(Self {
-            hijacker: Hijacker::new(config_path).context("Failed to initialize hijiacker")?,
+            hijacker: Arc::new(RwLock::new(hijacker)),
         })
     }
+
+    /// Re-reads `config_path` and swaps in a freshly built hijacker, so a `SIGHUP` can pick
+    /// up an edited `upatchd.yaml` (hijack target lists) without dropping the RPC socket or
+    /// any in-flight handler sharing this `SkeletonImpl`'s `Arc`.
+    pub fn reload<P: AsRef<Path>>(&self, config_path: P) -> Result<()> {
+        let hijacker = Hijacker::new(config_path).context("Failed to initialize hijiacker")?;
+        *self.hijacker.write() = hijacker;
+
+        Ok(())
+    }
 }
 
 impl Skeleton for SkeletonImpl {
@@ -28,6 +45,7 @@ impl Skeleton for SkeletonImpl {
         RpcFunction::call(|| {
             info!("Enable hijack: \"{}\"", elf_path.display());
             self.hijacker
+                .read()
                 .hijack(&elf_path)
                 .with_context(|| format!("Failed to hijack \"{}\"", elf_path.display()))
         })
@@ -37,6 +55,7 @@ impl Skeleton for SkeletonImpl {
         RpcFunction::call(|| {
             info!("Disable hijack: \"{}\"", elf_path.display());
             self.hijacker
+                .read()
                 .release(&elf_path)
                 .with_context(|| format!("Failed to release hijack for \"{}\"", elf_path.display()))
         })